@@ -67,6 +67,12 @@ fn load_resource_assets(world: &mut World) {
                     info!("asset loaded: {handle:?}");
                     insert_fn(world, &handle);
                     resource_handles.finished.push(handle);
+                } else if assets.recursive_dependency_load_state(&handle).is_failed() {
+                    // Don't block the loading screen forever, the users of the resource
+                    // are expected to cope with the missing assets.
+                    error!("asset failed to load: {handle:?}");
+                    insert_fn(world, &handle);
+                    resource_handles.finished.push(handle);
                 } else {
                     // debug!("waiting... {handle:?}");
                     resource_handles.waiting.push_back((handle, insert_fn));
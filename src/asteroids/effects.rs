@@ -0,0 +1,285 @@
+//! Looks and sounds of the asteroids, left out of headless simulations.
+
+use std::time::Duration;
+
+use avian2d::prelude::Physics;
+use bevy::{
+    color::palettes::css::WHITE,
+    ecs::relationship::RelatedSpawnerCommands,
+    mesh::{SphereKind, SphereMeshBuilder},
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::AsBindGroup,
+};
+use bevy_kira_audio::AudioChannel;
+use bevy_tweening::{
+    AnimCompletedEvent, AnimTarget, Tween, TweenAnim,
+    lens::{TransformRotationLens, TransformScaleLens},
+};
+
+use crate::{
+    audio::{AudioAssets, SfxChannel, spatial::SpatialAudio},
+    screens::Screen,
+    utils::{PointLightLens, StandardMaterialLens},
+    vfx::ScreenShake,
+};
+
+use super::{Asteroid, AsteroidDestroyed};
+
+const ASTEROID_SHADER_PATH: &str = "shaders/asteroid.wgsl";
+
+/// Volume of an asteroid shattering in an explosion, quieter than a collision with the ship.
+const SHATTER_VOLUME: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((MaterialPlugin::<
+        ExtendedMaterial<StandardMaterial, AsteroidMaterial>,
+    >::default(),))
+        .add_observer(on_add_asteroid)
+        .add_systems(Update, play_destruction.run_if(in_state(Screen::Gameplay)));
+}
+
+#[derive(Component)]
+pub struct AnimatedExplosion;
+
+fn on_add_asteroid(
+    trigger: On<Add, Asteroid>,
+    mut commands: Commands,
+    asteroids: Query<&Asteroid>,
+
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut asteroid_materials: ResMut<Assets<ExtendedMaterial<StandardMaterial, AsteroidMaterial>>>,
+) {
+    let entity = trigger.event().event_target();
+    let Ok(asteroid) = asteroids.get(entity) else {
+        return;
+    };
+
+    commands.entity(entity).insert((
+        Mesh3d(meshes.add(Sphere::new(asteroid.radius))),
+        MeshMaterial3d(asteroid_materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: WHITE.into(),
+                // emissive: GREEN.into(),
+                ..Default::default()
+            },
+
+            extension: AsteroidMaterial {
+                terrain_seed: Vec4::new(rand::random::<f32>(), rand::random::<f32>(), 0.0, 0.0)
+                    * 10.,
+                radius: Vec4::splat(asteroid.radius),
+            },
+        })),
+    ));
+}
+
+/// Explodes the destroyed asteroids on screen, ramming one also shakes the camera.
+fn play_destruction(
+    mut commands: Commands,
+    mut destroyed: MessageReader<AsteroidDestroyed>,
+
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut screen_shake: ResMut<ScreenShake>,
+    audio: Res<AudioChannel<SfxChannel>>,
+    spatial_audio: SpatialAudio,
+    audio_assets: Res<AudioAssets>,
+    time: Res<Time<Physics>>,
+) {
+    for asteroid in destroyed.read() {
+        let volume = if asteroid.hit_ship {
+            1.0
+        } else {
+            SHATTER_VOLUME
+        };
+        spatial_audio.play_at(
+            &mut commands,
+            &audio,
+            audio_assets.explosion.clone(),
+            asteroid.pos,
+            volume,
+        );
+        spawn_asteroid_explosion(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            asteroid.pos,
+            asteroid.radius,
+        );
+
+        if asteroid.hit_ship {
+            screen_shake.until = time.elapsed_secs() + 0.5;
+        }
+    }
+}
+
+fn spawn_asteroid_explosion(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    translation: Vec3,
+    radius: f32,
+) {
+    commands
+        .spawn((
+            Transform::from_translation(translation),
+            Visibility::Visible,
+        ))
+        .with_children(|builder| {
+            spawn_animated_explosion(
+                builder,
+                meshes,
+                materials,
+                radius,
+                Srgba::new(1.0, 0.7, 0.7, 1.0),
+                Srgba::new(1.0, 0.1, 0.1, 0.0),
+                Duration::from_millis(1200),
+                true,
+            );
+            spawn_animated_explosion(
+                builder,
+                meshes,
+                materials,
+                radius * 0.5,
+                Srgba::new(1.0, 0.7, 0.7, 1.0),
+                Srgba::new(1.0, 0.7, 0.7, 0.0),
+                Duration::from_millis(900),
+                false,
+            );
+
+            spawn_animated_explosion(
+                builder,
+                meshes,
+                materials,
+                radius * 0.2,
+                Srgba::new(1.0, 0.7, 0.7, 1.0),
+                Srgba::new(1.0, 0.9, 0.9, 0.6),
+                Duration::from_millis(700),
+                false,
+            )
+        });
+}
+
+fn spawn_animated_explosion(
+    builder: &mut RelatedSpawnerCommands<'_, ChildOf>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    radius: f32,
+    color_start: Srgba,
+    color_end: Srgba,
+    duration: Duration,
+    despawn_parent: bool,
+) {
+    let parent = builder.target_entity();
+    let sphere = SphereMeshBuilder::new(radius, SphereKind::Ico { subdivisions: 2 }).build();
+
+    let rotation_anim = Tween::new(
+        EaseFunction::QuinticOut,
+        duration,
+        TransformRotationLens {
+            start: Quat::IDENTITY,
+            end: Quat::from_axis_angle(Vec3::Z, std::f32::consts::PI * 6.),
+        },
+    );
+    let scale_anim = Tween::new(
+        EaseFunction::QuinticOut,
+        duration,
+        TransformScaleLens {
+            start: Vec3::splat(1.0),
+            end: Vec3::splat(5.5),
+        },
+    );
+
+    let point_light_tween = Tween::new(
+        EaseFunction::ExponentialIn,
+        duration,
+        PointLightLens {
+            color_start: color_start.into(),
+            color_end: color_end.into(),
+            intensity_start: 10000000000000.,
+            intensity_end: 0.,
+        },
+    );
+
+    let color_tween = Tween::new(
+        EaseFunction::QuinticOut,
+        duration,
+        StandardMaterialLens {
+            color_start: color_start.into(),
+            color_end: color_end.into(),
+            emissive_start: (color_start * 10.).into(),
+            emissive_end: (color_end * 10.).into(),
+        },
+    );
+
+    let asteroid_material = materials.add(StandardMaterial {
+        alpha_mode: AlphaMode::Blend,
+        ..Default::default()
+    });
+
+    let asteroid_material_id = asteroid_material.id();
+
+    let anim_entity_id = builder
+        .spawn((
+            AnimatedExplosion,
+            Mesh3d(meshes.add(sphere)),
+            MeshMaterial3d(asteroid_material),
+            PointLight {
+                color: color_start.into(),
+                radius: 1000.,
+                ..default()
+            },
+        ))
+        .id();
+
+    builder.spawn((
+        TweenAnim::new(rotation_anim),
+        AnimTarget::component::<Transform>(anim_entity_id),
+    ));
+
+    builder.spawn((
+        TweenAnim::new(scale_anim),
+        AnimTarget::component::<Transform>(anim_entity_id),
+    ));
+
+    builder.spawn((
+        TweenAnim::new(point_light_tween),
+        AnimTarget::component::<PointLight>(anim_entity_id),
+    ));
+
+    let mut color_anim = builder.spawn((
+        TweenAnim::new(color_tween),
+        AnimTarget::asset(asteroid_material_id),
+    ));
+
+    if despawn_parent {
+        color_anim.observe(
+            move |_trigger: On<AnimCompletedEvent>, mut commands: Commands| {
+                debug!("Despawning asteroid on anim completed event");
+                commands
+                    .get_entity(parent)
+                    .map(|mut ec| ec.try_despawn())
+                    .ok();
+            },
+        );
+    }
+}
+
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct AsteroidMaterial {
+    #[uniform(100)]
+    terrain_seed: Vec4,
+
+    #[uniform(101)]
+    radius: Vec4,
+}
+
+impl MaterialExtension for AsteroidMaterial {
+    fn vertex_shader() -> bevy::shader::ShaderRef {
+        ASTEROID_SHADER_PATH.into()
+    }
+
+    // fn fragment_shader() -> bevy::render::render_resource::ShaderRef {
+    //     ASTEROID_SHADER_PATH.into()
+    // }
+}
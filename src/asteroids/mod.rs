@@ -1,56 +1,82 @@
-use std::time::Duration;
-
-use avian2d::prelude::{Collider, CollisionEventsEnabled, CollisionStart, Physics, RigidBody};
-use bevy::{
-    color::palettes::css::WHITE,
-    ecs::relationship::RelatedSpawnerCommands,
-    mesh::{SphereKind, SphereMeshBuilder},
-    pbr::{ExtendedMaterial, MaterialExtension},
-    prelude::*,
-    render::render_resource::AsBindGroup,
-};
-use bevy_kira_audio::{Audio, AudioControl};
-use bevy_tweening::{
-    AnimCompletedEvent, AnimTarget, Tween, TweenAnim,
-    lens::{TransformRotationLens, TransformScaleLens},
+use avian2d::prelude::{
+    AngularVelocity, Collider, CollisionEventsEnabled, CollisionStart, LinearVelocity, Physics,
+    RigidBody,
 };
+use bevy::prelude::*;
+use rand::{RngExt as _, SeedableRng, rngs::SmallRng};
 
 use crate::{
-    audio::AudioAssets,
-    player::{Player, movement::AuraEarned},
-    utils::{PointLightLens, StandardMaterialLens},
-    vfx::ScreenShake,
+    PausableSystems,
+    player::{Player, hull::Hull},
+    red_gas::RedOrbExplosion,
+    screens::Screen,
+    simulation::is_headless,
+    space::delta::{ChunkDeltas, ChunkItem},
 };
 
-const ASTEROID_SHADER_PATH: &str = "shaders/asteroid.wgsl";
+pub mod effects;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((MaterialPlugin::<
-        ExtendedMaterial<StandardMaterial, AsteroidMaterial>,
-    >::default(),))
+    app.add_message::<AsteroidDestroyed>()
         .add_observer(on_add_asteroid)
-        .add_observer(on_add_ship_asteroid_collider);
+        .add_observer(on_add_ship_asteroid_collider)
+        .add_systems(
+            FixedUpdate,
+            shatter_asteroids
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
+
+    if !is_headless(app) {
+        app.add_plugins(effects::plugin);
+    }
 }
 
-#[derive(Component)]
+/// Maximum drift speed of a generated asteroid.
+pub const MAX_DRIFT_SPEED: f32 = 30.0;
+/// Maximum spin of a generated asteroid, in rad/s.
+pub const MAX_SPIN: f32 = 0.8;
+/// Asteroids smaller than this are destroyed without leaving fragments.
+const MIN_FRAGMENT_RADIUS: f32 = 8.0;
+/// Number of fragments an asteroid shatters into.
+const FRAGMENT_COUNT: usize = 3;
+/// Speed fragments are thrown away from each other at.
+const FRAGMENT_SPEED: f32 = 60.0;
+/// Fresh fragments can't shatter right away, or an explosion would grind them down in a single frame.
+const FRAGMENT_SHATTER_DELAY_SECS: f32 = 0.6;
+
+#[derive(Component, Clone, Debug)]
 pub struct Asteroid {
     pub pos: Vec3,
     pub radius: f32,
+    pub velocity: Vec2,
+    /// Angular velocity, in rad/s.
+    pub spin: f32,
 }
 
+/// A piece of a shattered asteroid, it can shatter further once [`Self::breakable_at`] has passed.
 #[derive(Component)]
-pub struct ShipAsteroidCollider;
+pub struct AsteroidFragment {
+    /// Physics time in seconds.
+    pub breakable_at: f32,
+}
 
 #[derive(Component)]
-pub struct AnimatedExplosion;
+pub struct ShipAsteroidCollider;
+
+/// An asteroid got destroyed, the [`effects`] make it explode on screen.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct AsteroidDestroyed {
+    pub pos: Vec3,
+    pub radius: f32,
+    /// Rammed by the ship rather than caught by an explosion.
+    pub hit_ship: bool,
+}
 
 fn on_add_asteroid(
     trigger: On<Add, Asteroid>,
     mut commands: Commands,
     asteroids: Query<&Asteroid>,
-
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut asteroid_materials: ResMut<Assets<ExtendedMaterial<StandardMaterial, AsteroidMaterial>>>,
 ) {
     let entity = trigger.event().event_target();
     let Ok(asteroid) = asteroids.get(entity) else {
@@ -58,7 +84,9 @@ fn on_add_asteroid(
     };
 
     commands.entity(entity).insert((
-        RigidBody::Kinematic,
+        RigidBody::Dynamic,
+        LinearVelocity(asteroid.velocity),
+        AngularVelocity(asteroid.spin),
         // // CollisionLayers::new(
         // //     GameCollisionLayers::Meteorites,
         // //     GameCollisionLayers::Meteorites,
@@ -66,24 +94,91 @@ fn on_add_asteroid(
         Collider::circle(asteroid.radius * 0.85),
         Transform::from_translation(asteroid.pos),
         // .with_scale(Vec3::splat(meteorite_size)),
-        Mesh3d(meshes.add(Sphere::new(asteroid.radius))),
-        MeshMaterial3d(asteroid_materials.add(ExtendedMaterial {
-            base: StandardMaterial {
-                base_color: WHITE.into(),
-                // emissive: GREEN.into(),
-                ..Default::default()
-            },
-
-            extension: AsteroidMaterial {
-                terrain_seed: Vec4::new(rand::random::<f32>(), rand::random::<f32>(), 0.0, 0.0)
-                    * 10.,
-                radius: Vec4::splat(asteroid.radius),
-            },
-        })),
     ));
 }
 
-const ASTEROID_AURA_LOSS: f32 = 100.0;
+/// Breaks the asteroids caught by red orb explosions into smaller fragments.
+fn shatter_asteroids(
+    mut commands: Commands,
+    explosions: Query<&RedOrbExplosion>,
+    asteroids: Query<(
+        Entity,
+        &Asteroid,
+        &Transform,
+        &LinearVelocity,
+        Option<&ChildOf>,
+        Option<&ChunkItem>,
+        Option<&AsteroidFragment>,
+    )>,
+    mut deltas: ResMut<ChunkDeltas>,
+    mut destroyed: MessageWriter<AsteroidDestroyed>,
+    time: Res<Time<Physics>>,
+) {
+    if explosions.is_empty() {
+        return;
+    }
+
+    let now = time.elapsed_secs();
+
+    for (entity, asteroid, tr, velocity, parent, chunk_item, fragment) in &asteroids {
+        if fragment.is_some_and(|f| f.breakable_at > now) {
+            continue;
+        }
+
+        let pos = tr.translation;
+        let caught = explosions.iter().any(|explosion| {
+            let reach = explosion.radius + asteroid.radius;
+            explosion.pos.distance_squared(pos.truncate()) < reach * reach
+        });
+        if !caught {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        if let Some(item) = chunk_item {
+            deltas.record(item);
+        }
+        destroyed.write(AsteroidDestroyed {
+            pos,
+            radius: asteroid.radius,
+            hit_ship: false,
+        });
+
+        let radius = asteroid.radius * 0.5;
+        if radius < MIN_FRAGMENT_RADIUS {
+            continue;
+        }
+
+        // seeded by where the asteroid broke, so the fragments fly the same way in a replay
+        let mut rng =
+            SmallRng::seed_from_u64(((pos.x.to_bits() as u64) << 32) | pos.y.to_bits() as u64);
+
+        // evenly spread around the center, randomly rotated
+        let angle_offset = rng.random::<f32>() * std::f32::consts::TAU;
+        for i in 0..FRAGMENT_COUNT {
+            let angle = angle_offset + i as f32 / FRAGMENT_COUNT as f32 * std::f32::consts::TAU;
+            let dir = Vec2::from_angle(angle);
+
+            let mut fragment = commands.spawn((
+                Asteroid {
+                    pos: pos + (dir * radius).extend(0.0),
+                    radius,
+                    velocity: velocity.0 + dir * FRAGMENT_SPEED,
+                    spin: (rng.random::<f32>() - 0.5) * 4.0 * MAX_SPIN,
+                },
+                AsteroidFragment {
+                    breakable_at: now + FRAGMENT_SHATTER_DELAY_SECS,
+                },
+            ));
+
+            // fragments start in the chunk of their asteroid, and follow the chunks from there
+            match parent {
+                Some(parent) => fragment.insert(ChildOf(parent.parent())),
+                None => fragment.insert(DespawnOnExit(Screen::Gameplay)),
+            };
+        }
+    }
+}
 
 fn on_add_ship_asteroid_collider(trigger: On<Add, ShipAsteroidCollider>, mut commands: Commands) {
     let entity = trigger.event().entity;
@@ -94,195 +189,39 @@ fn on_add_ship_asteroid_collider(trigger: On<Add, ShipAsteroidCollider>, mut com
         .observe(
             |trigger: On<CollisionStart>,
              mut commands: Commands,
-             mut player: Single<&mut Player>,
-             asteroids: Query<(&Asteroid, &Transform)>,
-
-             mut meshes: ResMut<Assets<Mesh>>,
-             mut materials: ResMut<Assets<StandardMaterial>>,
-             mut screen_shake: ResMut<ScreenShake>,
-             mut aura_event: MessageWriter<AuraEarned>,
-             audio: Res<Audio>,
-             audio_assets: Res<AudioAssets>,
+             player: Single<(&mut Player, &mut Hull, &LinearVelocity)>,
+             asteroids: Query<(&Asteroid, &Transform, &LinearVelocity, Option<&ChunkItem>)>,
+             mut deltas: ResMut<ChunkDeltas>,
+             mut destroyed: MessageWriter<AsteroidDestroyed>,
              time: Res<Time<Physics>>| {
-                let Ok((asteroid, asteroid_transform)) = asteroids.get(trigger.event().collider2)
+                let Ok((asteroid, asteroid_transform, asteroid_velocity, chunk_item)) =
+                    asteroids.get(trigger.event().collider2)
                 else {
                     return;
                 };
+                let (mut player, mut hull, ship_velocity) = player.into_inner();
 
                 debug!("collision");
 
-                audio.play(audio_assets.explosion.clone());
+                let impact_speed = (ship_velocity.0 - asteroid_velocity.0).length();
+                let lost = hull.hit(Hull::impact_damage(impact_speed), time.elapsed_secs());
+                // a graze or a hit while invulnerable just bounces the asteroid off
+                if lost <= 0.0 {
+                    return;
+                }
+                debug!("asteroid impact at {impact_speed:.0}: hull -{lost:.0}");
 
                 commands.entity(trigger.event().collider2).despawn();
-
-                player.aura_points = (player.aura_points - ASTEROID_AURA_LOSS).max(0.0);
-                aura_event.write(AuraEarned(-ASTEROID_AURA_LOSS));
+                if let Some(item) = chunk_item {
+                    deltas.record(item);
+                }
                 player.near_asteroids = false;
 
-                commands
-                    .spawn((
-                        Transform::from_translation(asteroid_transform.translation),
-                        Visibility::Visible,
-                    ))
-                    .with_children(|builder| {
-                        spawn_animated_explosion(
-                            builder,
-                            &mut meshes,
-                            &mut materials,
-                            asteroid.radius,
-                            Srgba::new(1.0, 0.7, 0.7, 1.0),
-                            Srgba::new(1.0, 0.1, 0.1, 0.0),
-                            Duration::from_millis(1200),
-                            true,
-                        );
-                        spawn_animated_explosion(
-                            builder,
-                            &mut meshes,
-                            &mut materials,
-                            asteroid.radius * 0.5,
-                            Srgba::new(1.0, 0.7, 0.7, 1.0),
-                            Srgba::new(1.0, 0.7, 0.7, 0.0),
-                            Duration::from_millis(900),
-                            false,
-                        );
-
-                        spawn_animated_explosion(
-                            builder,
-                            &mut meshes,
-                            &mut materials,
-                            asteroid.radius * 0.2,
-                            Srgba::new(1.0, 0.7, 0.7, 1.0),
-                            Srgba::new(1.0, 0.9, 0.9, 0.6),
-                            Duration::from_millis(700),
-                            false,
-                        )
-                    });
-
-                screen_shake.until = time.elapsed_secs() + 0.5;
+                destroyed.write(AsteroidDestroyed {
+                    pos: asteroid_transform.translation,
+                    radius: asteroid.radius,
+                    hit_ship: true,
+                });
             },
         );
 }
-
-fn spawn_animated_explosion(
-    builder: &mut RelatedSpawnerCommands<'_, ChildOf>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    radius: f32,
-    color_start: Srgba,
-    color_end: Srgba,
-    duration: Duration,
-    despawn_parent: bool,
-) {
-    let parent = builder.target_entity();
-    let sphere = SphereMeshBuilder::new(radius, SphereKind::Ico { subdivisions: 2 }).build();
-
-    let rotation_anim = Tween::new(
-        EaseFunction::QuinticOut,
-        duration,
-        TransformRotationLens {
-            start: Quat::IDENTITY,
-            end: Quat::from_axis_angle(Vec3::Z, std::f32::consts::PI * 6.),
-        },
-    );
-    let scale_anim = Tween::new(
-        EaseFunction::QuinticOut,
-        duration,
-        TransformScaleLens {
-            start: Vec3::splat(1.0),
-            end: Vec3::splat(5.5),
-        },
-    );
-
-    let point_light_tween = Tween::new(
-        EaseFunction::ExponentialIn,
-        duration,
-        PointLightLens {
-            color_start: color_start.into(),
-            color_end: color_end.into(),
-            intensity_start: 10000000000000.,
-            intensity_end: 0.,
-        },
-    );
-
-    let color_tween = Tween::new(
-        EaseFunction::QuinticOut,
-        duration,
-        StandardMaterialLens {
-            color_start: color_start.into(),
-            color_end: color_end.into(),
-            emissive_start: (color_start * 10.).into(),
-            emissive_end: (color_end * 10.).into(),
-        },
-    );
-
-    let asteroid_material = materials.add(StandardMaterial {
-        alpha_mode: AlphaMode::Blend,
-        ..Default::default()
-    });
-
-    let asteroid_material_id = asteroid_material.id();
-
-    let anim_entity_id = builder
-        .spawn((
-            AnimatedExplosion,
-            Mesh3d(meshes.add(sphere)),
-            MeshMaterial3d(asteroid_material),
-            PointLight {
-                color: color_start.into(),
-                radius: 1000.,
-                ..default()
-            },
-        ))
-        .id();
-
-    builder.spawn((
-        TweenAnim::new(rotation_anim),
-        AnimTarget::component::<Transform>(anim_entity_id),
-    ));
-
-    builder.spawn((
-        TweenAnim::new(scale_anim),
-        AnimTarget::component::<Transform>(anim_entity_id),
-    ));
-
-    builder.spawn((
-        TweenAnim::new(point_light_tween),
-        AnimTarget::component::<PointLight>(anim_entity_id),
-    ));
-
-    let mut color_anim = builder.spawn((
-        TweenAnim::new(color_tween),
-        AnimTarget::asset(asteroid_material_id),
-    ));
-
-    if despawn_parent {
-        color_anim.observe(
-            move |_trigger: On<AnimCompletedEvent>, mut commands: Commands| {
-                debug!("Despawning asteroid on anim completed event");
-                commands
-                    .get_entity(parent)
-                    .map(|mut ec| ec.try_despawn())
-                    .ok();
-            },
-        );
-    }
-}
-
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
-pub struct AsteroidMaterial {
-    #[uniform(100)]
-    terrain_seed: Vec4,
-
-    #[uniform(101)]
-    radius: Vec4,
-}
-
-impl MaterialExtension for AsteroidMaterial {
-    fn vertex_shader() -> bevy::shader::ShaderRef {
-        ASTEROID_SHADER_PATH.into()
-    }
-
-    // fn fragment_shader() -> bevy::render::render_resource::ShaderRef {
-    //     ASTEROID_SHADER_PATH.into()
-    // }
-}
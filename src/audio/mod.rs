@@ -1,27 +1,61 @@
-use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioSource, AudioTween, PlaybackState};
+use avian2d::prelude::{Physics, PhysicsTime};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_kira_audio::{AudioApp, AudioChannel, AudioControl, AudioSource, prelude::Decibels};
 
-use crate::{asset_tracking::LoadResource, screens::Screen};
+use crate::{asset_tracking::LoadResource, settings::Settings};
+
+pub mod music;
+pub mod spatial;
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<AudioAssets>();
-    app.init_resource::<MusicHandle>();
+    app.add_audio_channel::<MusicChannel>()
+        .add_audio_channel::<SfxChannel>()
+        .add_audio_channel::<UiChannel>()
+        .add_audio_channel::<AmbienceChannel>();
+    app.add_plugins((music::plugin, spatial::plugin));
+
+    app.add_systems(Update, follow_physics_speed);
+}
+
+// Every sound plays on one of these kira channels rather than the default `Audio` one,
+// so their playback rate can be controlled separately.
+// The volume settings are part of the volume of every sound, see `linear_to_decibels`.
+
+/// Kira channel of the music.
+#[derive(Resource)]
+pub struct MusicChannel;
 
-    // app.add_systems(Update, play_loop.run_if(resource_added::<AudioAssets>));
+/// Kira channel of the one-shot sounds happening in the world, e.g. explosions.
+#[derive(Resource)]
+pub struct SfxChannel;
 
-    app.add_systems(OnEnter(Screen::Gameplay), resume_music);
-    app.add_systems(OnExit(Screen::Gameplay), pause_music);
-    app.add_systems(
-        Update,
-        apply_global_volume.run_if(resource_changed::<GlobalVolume>),
-    );
+/// Kira channel of the menu sounds, they keep playing at normal speed during bullet time.
+#[derive(Resource)]
+pub struct UiChannel;
+
+/// Kira channel of the looping sounds of the world, e.g. the engine.
+#[derive(Resource)]
+pub struct AmbienceChannel;
+
+/// The channels of the sounds happening in the world, as opposed to the music and the menus.
+#[derive(SystemParam)]
+pub struct WorldAudio<'w> {
+    pub sfx: Res<'w, AudioChannel<SfxChannel>>,
+    pub ambience: Res<'w, AudioChannel<AmbienceChannel>>,
+}
+
+impl WorldAudio<'_> {
+    /// Slows down (or speeds up) the world sounds, e.g. for bullet time.
+    pub fn set_playback_rate(&self, rate: f64) {
+        self.sfx.set_playback_rate(rate);
+        self.ambience.set_playback_rate(rate);
+    }
 }
 
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct AudioAssets {
-    #[dependency]
-    pub music: Handle<AudioSource>,
     #[dependency]
     pub explosion: Handle<AudioSource>,
     #[dependency]
@@ -60,7 +94,6 @@ impl FromWorld for AudioAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
         Self {
-            music: assets.load("audio/music/music.ogg"),
             explosion: assets.load("audio/sound_effects/explosion.ogg"),
             engine_fire: assets.load("audio/sound_effects/engine_fire.ogg"),
             lose: assets.load("audio/sound_effects/lose.ogg"),
@@ -81,37 +114,24 @@ impl FromWorld for AudioAssets {
     }
 }
 
-#[derive(Resource, Default)]
-pub struct MusicHandle(Handle<AudioInstance>);
-
-fn pause_music(mut audio_instances: ResMut<Assets<AudioInstance>>, handle: Res<MusicHandle>) {
-    let Some(music) = audio_instances.get_mut(&handle.0) else {
-        return;
-    };
-
-    music.pause(AudioTween::default());
-}
-
-fn resume_music(mut audio_instances: ResMut<Assets<AudioInstance>>, handle: Res<MusicHandle>) {
-    let Some(music) = audio_instances.get_mut(&handle.0) else {
-        return;
-    };
-
-    music.resume(AudioTween::default());
-}
-
-fn play_loop(mut commands: Commands, assets: Res<AudioAssets>, audio: Res<Audio>) {
-    debug!("playing loop...");
-    let handle = audio
-        .play(assets.music.clone())
-        .paused()
-        .with_volume(0.3)
-        .looped()
-        .handle();
-    commands.insert_resource(MusicHandle(handle));
+/// Slows the world sounds down along with the physics, e.g. in bullet time.
+fn follow_physics_speed(
+    physics_time: Res<Time<Physics>>,
+    world: WorldAudio,
+    mut playback_rate: Local<Option<f64>>,
+) {
+    let rate = physics_time.relative_speed_f64();
+    if *playback_rate != Some(rate) {
+        world.set_playback_rate(rate);
+        *playback_rate = Some(rate);
+    }
 }
 
-/// [`GlobalVolume`] doesn't apply to already-running audio entities, so this system will update them.
-fn apply_global_volume(_audio: Res<Audio>) {
-    // audio.set_volume(GlobalVolume::get());
+/// Converts a linear volume, like the ones in [`Settings`], to the decibels kira expects.
+pub fn linear_to_decibels(volume: f32) -> Decibels {
+    if volume <= 0.0 {
+        Decibels::SILENCE
+    } else {
+        Decibels(20.0 * volume.log10())
+    }
 }
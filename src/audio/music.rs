@@ -0,0 +1,235 @@
+//! Gameplay music, layered from stems that fade in and out with what is happening.
+//!
+//! The stems are loaded outside of [`LoadResource`](crate::asset_tracking::LoadResource),
+//! so a missing track never holds the loading screen. Once every stem is loaded or failed,
+//! the available ones start together (to stay in sync) and the missing ones are skipped with a warning.
+
+use std::time::Duration;
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioChannel, AudioControl, AudioInstance, AudioSource, AudioTween};
+
+use crate::{
+    Pause,
+    player::{Player, movement::AuraEarned},
+    red_gas::RedOrbExplosion,
+    screens::Screen,
+    settings::Settings,
+};
+
+use super::{MusicChannel, linear_to_decibels};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicStems>()
+        .init_resource::<Music>()
+        .init_resource::<AuraStreak>()
+        .add_systems(OnEnter(Screen::Gameplay), resume_music)
+        .add_systems(OnExit(Screen::Gameplay), pause_music)
+        .add_systems(
+            Update,
+            (start_music, track_aura_streak, mix_stems)
+                .chain()
+                .run_if(in_state(Screen::Gameplay)),
+        );
+}
+
+/// Time for a stem to fade from silence to full volume.
+const STEM_FADE_SECS: f32 = 1.5;
+/// Fade out when leaving the gameplay, e.g. on death.
+const STOP_FADE: Duration = Duration::from_secs(2);
+/// Ship speeds over which the speed stem fades in.
+const SPEED_STEM_RANGE: (f32, f32) = (200.0, 800.0);
+/// Aura earned in a short time for the aura stem to play at full volume.
+const FULL_AURA_STREAK: f32 = 150.0;
+/// Fraction of the aura streak that remains after one second.
+const AURA_STREAK_RETENTION: f32 = 0.3;
+/// Red orb explosions closer than this (to their edge) bring in the danger stem.
+const DANGER_DISTANCE: f32 = 600.0;
+/// Volume of the base stem while the game is paused, the other stems are silent.
+const PAUSED_LEVEL: f32 = 0.4;
+
+/// A layer of the gameplay music.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stem {
+    /// Always playing.
+    Base,
+    Speed,
+    Aura,
+    Danger,
+}
+
+impl Stem {
+    pub const ALL: [Stem; 4] = [Stem::Base, Stem::Speed, Stem::Aura, Stem::Danger];
+
+    fn path(self) -> &'static str {
+        match self {
+            Stem::Base => "audio/music/music.ogg",
+            Stem::Speed => "audio/music/speed.ogg",
+            Stem::Aura => "audio/music/aura.ogg",
+            Stem::Danger => "audio/music/danger.ogg",
+        }
+    }
+
+    /// Volume of the stem, from 0.0 to 1.0.
+    /// `danger` goes from 0.0 when no explosion is nearby to 1.0 when the ship is in one.
+    pub fn target_level(self, speed: f32, aura_streak: f32, danger: f32, paused: bool) -> f32 {
+        if paused {
+            return if self == Stem::Base {
+                PAUSED_LEVEL
+            } else {
+                0.0
+            };
+        }
+
+        match self {
+            Stem::Base => 1.0,
+            Stem::Speed => {
+                let (min, max) = SPEED_STEM_RANGE;
+                ((speed - min) / (max - min)).clamp(0.0, 1.0)
+            }
+            Stem::Aura => (aura_streak / FULL_AURA_STREAK).clamp(0.0, 1.0),
+            Stem::Danger => danger.clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MusicStems(Vec<(Stem, Handle<AudioSource>)>);
+
+impl FromWorld for MusicStems {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self(
+            Stem::ALL
+                .into_iter()
+                .map(|stem| (stem, assets.load(stem.path())))
+                .collect(),
+        )
+    }
+}
+
+/// The playing stems.
+#[derive(Resource, Default)]
+pub struct Music {
+    started: bool,
+    /// Instance and current volume of every available stem.
+    stems: Vec<(Stem, Handle<AudioInstance>, f32)>,
+}
+
+/// Aura earned recently, it decays over time.
+#[derive(Resource, Default)]
+pub struct AuraStreak(pub f32);
+
+/// Starts the stems once they are all settled, so they play in sync.
+fn start_music(
+    mut music: ResMut<Music>,
+    stems: Res<MusicStems>,
+    asset_server: Res<AssetServer>,
+    channel: Res<AudioChannel<MusicChannel>>,
+) {
+    if music.started {
+        return;
+    }
+
+    let settled = stems.0.iter().all(|(_, handle)| {
+        let state = asset_server.load_state(handle);
+        state.is_loaded() || state.is_failed()
+    });
+    if !settled {
+        return;
+    }
+
+    music.started = true;
+    for (stem, handle) in &stems.0 {
+        if !asset_server.load_state(handle).is_loaded() {
+            warn!(
+                "music stem {stem:?} is missing ({}), playing without it",
+                stem.path()
+            );
+            continue;
+        }
+
+        let instance = channel
+            .play(handle.clone())
+            .with_volume(linear_to_decibels(0.0))
+            .looped()
+            .handle();
+        music.stems.push((*stem, instance, 0.0));
+    }
+}
+
+fn track_aura_streak(
+    mut streak: ResMut<AuraStreak>,
+    mut aura_events: MessageReader<AuraEarned>,
+    time: Res<Time>,
+) {
+    // spending aura doesn't break the streak
+    let earned: f32 = aura_events.read().map(|e| e.0.max(0.0)).sum();
+    streak.0 = streak.0 * AURA_STREAK_RETENTION.powf(time.delta_secs()) + earned;
+}
+
+/// Fades every stem towards the volume the situation calls for.
+fn mix_stems(
+    mut music: ResMut<Music>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    player: Query<(&Transform, &LinearVelocity), With<Player>>,
+    explosions: Query<&RedOrbExplosion>,
+    streak: Res<AuraStreak>,
+    pause: Res<State<Pause>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let (speed, danger) = match player.single() {
+        Ok((tr, velocity)) => {
+            let pos = tr.translation.truncate();
+            let closest = explosions
+                .iter()
+                .map(|explosion| (explosion.pos.distance(pos) - explosion.radius).max(0.0))
+                .min_by(|a, b| a.total_cmp(b))
+                .unwrap_or(f32::MAX);
+            (velocity.length(), 1.0 - closest / DANGER_DISTANCE)
+        }
+        Err(_) => (0.0, 0.0),
+    };
+    let paused = pause.get().0;
+
+    let delta = time.delta_secs();
+    let max_step = delta / STEM_FADE_SECS;
+    for (stem, handle, level) in &mut music.stems {
+        let target = stem.target_level(speed, streak.0, danger, paused);
+        *level += (target - *level).clamp(-max_step, max_step);
+
+        // the volume of an instance replaces the one of the channel
+        if let Some(instance) = instances.get_mut(handle.id()) {
+            instance.set_decibels(
+                linear_to_decibels(*level * settings.music_gain()),
+                AudioTween::linear(Duration::from_secs_f32(delta)),
+            );
+        }
+    }
+}
+
+fn pause_music(mut instances: ResMut<Assets<AudioInstance>>, music: Res<Music>) {
+    for (_, handle, _) in &music.stems {
+        if let Some(instance) = instances.get_mut(handle.id()) {
+            instance.pause(AudioTween::linear(STOP_FADE));
+        }
+    }
+}
+
+/// Resumes the stems from silence, they fade back in as the mix catches up.
+fn resume_music(
+    mut instances: ResMut<Assets<AudioInstance>>,
+    mut music: ResMut<Music>,
+    mut streak: ResMut<AuraStreak>,
+) {
+    streak.0 = 0.0;
+    for (_, handle, level) in &mut music.stems {
+        *level = 0.0;
+        if let Some(instance) = instances.get_mut(handle.id()) {
+            instance.set_decibels(linear_to_decibels(0.0), AudioTween::default());
+            instance.resume(AudioTween::default());
+        }
+    }
+}
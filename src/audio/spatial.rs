@@ -0,0 +1,164 @@
+//! Positional audio for the sounds of the world.
+//!
+//! A [`SoundEmitter`] plays its sound from the position of its entity, and the [`AudioListener`]
+//! (the camera) hears it with distance attenuation and stereo panning.
+//! Only the plane of the game counts, the height of the camera follows the speed and would
+//! make everything quieter when flying fast.
+//! The volume of an instance replaces the one of its channel, so the heard volume includes
+//! the effects volume of the [`Settings`].
+
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*, transform::TransformSystems};
+use bevy_kira_audio::{
+    AudioChannel, AudioControl, AudioInstance, AudioSource, AudioTween, PlaybackState,
+    prelude::Panning,
+};
+
+use crate::settings::Settings;
+
+use super::linear_to_decibels;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        PostUpdate,
+        (update_emitters, despawn_finished_sounds).after(TransformSystems::Propagate),
+    );
+}
+
+/// Sounds closer than this play at full volume.
+const REFERENCE_DISTANCE: f32 = 300.0;
+/// Sounds further than this are silent.
+const MAX_DISTANCE: f32 = 3000.0;
+/// Sideways distance at which a sound comes from one speaker only.
+const FULL_PAN_DISTANCE: f32 = 1200.0;
+
+/// The ears of the player, there should be only one.
+#[derive(Component, Default)]
+pub struct AudioListener;
+
+/// A sound playing from the position of this entity.
+#[derive(Component)]
+pub struct SoundEmitter {
+    pub instance: Handle<AudioInstance>,
+    /// Linear volume heard right next to the emitter, before the effects volume of the settings.
+    pub volume: f32,
+}
+
+/// Despawns the entity once its sound has finished playing.
+#[derive(Component)]
+pub struct OneShotSound;
+
+/// How loud a sound at `offset` from the listener is heard, from 0.0 to 1.0.
+pub fn attenuation(offset: Vec2) -> f32 {
+    let distance = offset.length();
+    if distance >= MAX_DISTANCE {
+        return 0.0;
+    }
+
+    // inverse distance, faded out towards the max distance so the sound doesn't cut off
+    let inverse = REFERENCE_DISTANCE / distance.max(REFERENCE_DISTANCE);
+    inverse * (1.0 - distance / MAX_DISTANCE)
+}
+
+/// Stereo position of a sound `sideways` to the right of the listener, from -1.0 (left) to 1.0 (right).
+pub fn panning(sideways: f32) -> f32 {
+    (sideways / FULL_PAN_DISTANCE).clamp(-1.0, 1.0)
+}
+
+/// Volume and panning of a sound at `pos`, as heard by the listener with the given effects volume.
+fn spatialize(
+    listener: Option<&GlobalTransform>,
+    pos: Vec3,
+    volume: f32,
+    sfx_gain: f32,
+) -> (f32, f32) {
+    let volume = volume * sfx_gain;
+    let Some(listener) = listener else {
+        return (volume, 0.0);
+    };
+
+    let offset = (pos - listener.translation()).truncate();
+    let sideways = offset.dot(listener.right().truncate());
+    (volume * attenuation(offset), panning(sideways))
+}
+
+/// Plays sounds positioned in the world.
+#[derive(SystemParam)]
+pub struct SpatialAudio<'w, 's> {
+    listener: Query<'w, 's, &'static GlobalTransform, With<AudioListener>>,
+    settings: Res<'w, Settings>,
+}
+
+impl SpatialAudio<'_, '_> {
+    /// Plays a sound once at the given position, it is spatialized like any [`SoundEmitter`].
+    pub fn play_at<T: Resource>(
+        &self,
+        commands: &mut Commands,
+        channel: &AudioChannel<T>,
+        source: Handle<AudioSource>,
+        pos: Vec3,
+        volume: f32,
+    ) {
+        let (heard, pan) = spatialize(
+            self.listener.single().ok(),
+            pos,
+            volume,
+            self.settings.sfx_gain(),
+        );
+        let instance = channel
+            .play(source)
+            .with_volume(linear_to_decibels(heard))
+            .with_panning(Panning(pan))
+            .handle();
+
+        commands.spawn((
+            Name::new("Sound"),
+            Transform::from_translation(pos),
+            SoundEmitter { instance, volume },
+            OneShotSound,
+        ));
+    }
+}
+
+fn update_emitters(
+    emitters: Query<(&SoundEmitter, &GlobalTransform)>,
+    listener: Query<&GlobalTransform, With<AudioListener>>,
+    mut instances: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let listener = listener.single().ok();
+    let tween = AudioTween::linear(Duration::from_secs_f32(time.delta_secs()));
+
+    for (emitter, tr) in &emitters {
+        let Some(instance) = instances.get_mut(emitter.instance.id()) else {
+            continue;
+        };
+
+        let (heard, pan) = spatialize(
+            listener,
+            tr.translation(),
+            emitter.volume,
+            settings.sfx_gain(),
+        );
+        instance.set_decibels(linear_to_decibels(heard), tween.clone());
+        instance.set_panning(Panning(pan), tween.clone());
+    }
+}
+
+fn despawn_finished_sounds(
+    mut commands: Commands,
+    sounds: Query<(Entity, &SoundEmitter), With<OneShotSound>>,
+    instances: Res<Assets<AudioInstance>>,
+) {
+    for (entity, emitter) in &sounds {
+        // the instance only exists once the channel has started the sound
+        let finished = instances
+            .get(emitter.instance.id())
+            .is_some_and(|instance| matches!(instance.state(), PlaybackState::Stopped));
+        if finished {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! A bot flying the ship, for the attract mode of the title screen and for balance testing.
+//!
+//! While the [`Autopilot`] resource exists, the bot replaces the input of the devices with its own
+//! [`TickInput`], so it can only do what a player could. It heads for the gas ahead, found in the
+//! spatial index of the orbs, and steers clear of asteroids, red orbs, explosions, fire and black holes.
+//!
+//! Left alone for [`ATTRACT_IDLE_SECS`] on its main menu, the title screen starts a run flown by
+//! the bot, in a world of its own. Any key or button brings the title screen back, so does the death
+//! of the ship, with the seed and the pending replay it had before. These runs never reach
+//! the death screen, so they don't get on the leaderboard, in the replay or in the ghost.
+//!
+//! `cargo run -- --autopilot [--seeds 20] [--first-seed 0] [--ticks 7680]` lets the bot play a run
+//! on each seed headlessly and prints the scores, to see how a change affects the balance.
+
+use avian2d::prelude::LinearVelocity;
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_spatial::{SpatialAccess, kdtree::KDTree2};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    asteroids::Asteroid,
+    cli::{ModeArgs, unknown_option},
+    input::{Action, NextTickInput, TickInput, TickInputSystems},
+    menus::Menu,
+    player::{
+        Player,
+        death::{PlayerDamage, player_alive},
+    },
+    red_gas::{RedGasOrb, RedOrbExplosion},
+    replay::ReplayMode,
+    screens::Screen,
+    simulation::{Simulation, is_headless},
+    space::{
+        WorldSeed,
+        gas::{GasOrb, fire::FireField},
+        gravity::{GravityBody, GravityKind},
+        reroll_world_seed,
+    },
+    theme::widget,
+};
+
+/// Seconds without any input on the title screen before the attract mode starts.
+pub const ATTRACT_IDLE_SECS: f32 = 20.0;
+
+/// Radius of the ship collider.
+const SHIP_RADIUS: f32 = 3.0;
+/// How far ahead the bot looks for gas and red orbs.
+const LOOKAHEAD: f32 = 400.0;
+/// Pull of the gas ahead, relative to keeping the heading.
+const GAS_PULL: f32 = 0.8;
+/// Asteroids closer than this (in seconds) to a collision are avoided.
+const ASTEROID_LOOKAHEAD_SECS: f32 = 0.8;
+/// Distance kept from the asteroids, smaller than the aura sensor so passing them still earns aura.
+const ASTEROID_MARGIN: f32 = 20.0;
+const ASTEROID_PUSH: f32 = 4.0;
+/// An asteroid this close to a collision (in seconds) is dodged with a dash.
+const DASH_SECS: f32 = 0.25;
+/// Red orbs closer than this to the path are avoided, the gas burning behind the ship could set them off.
+const RED_ORB_CLEARANCE: f32 = 60.0;
+const RED_ORB_PUSH: f32 = 1.5;
+/// Explosions are fled from when the ship gets closer than this to their edge.
+const EXPLOSION_MARGIN: f32 = 400.0;
+const EXPLOSION_PUSH: f32 = 3.0;
+/// Distances ahead the fire is looked for, straight ahead and on both sides.
+const FIRE_PROBES: [f32; 2] = [60.0, 120.0];
+const FIRE_PUSH: f32 = 1.0;
+const BLACK_HOLE_PUSH: f32 = 3.0;
+/// Turn input per radian between the nose and the wanted heading.
+const TURN_GAIN: f32 = 3.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedFirst,
+        fly_ship
+            .in_set(TickInputSystems::Script)
+            .run_if(resource_exists::<Autopilot>)
+            .run_if(in_state(Screen::Gameplay))
+            .run_if(player_alive),
+    );
+
+    // the attract mode belongs to the main menu of the title screen
+    if !is_headless(app) {
+        app.init_resource::<TitleIdle>()
+            .add_systems(OnEnter(Menu::Main), reset_title_idle)
+            .add_systems(
+                Update,
+                (
+                    start_attract_mode.run_if(in_state(Screen::Title).and(in_state(Menu::Main))),
+                    stop_attract_mode
+                        .run_if(in_state(Screen::Gameplay))
+                        .run_if(resource_exists::<AttractMode>),
+                ),
+            )
+            .add_systems(
+                OnEnter(Screen::Gameplay),
+                spawn_attract_banner.run_if(resource_exists::<AttractMode>),
+            )
+            .add_systems(
+                OnExit(Screen::Gameplay),
+                leave_attract_mode.run_if(resource_exists::<AttractMode>),
+            )
+            .add_systems(
+                OnEnter(Screen::Title),
+                restore_title
+                    .after(reroll_world_seed)
+                    .run_if(resource_exists::<AttractMode>),
+            );
+    }
+}
+
+/// The bot flies the ship while this exists.
+#[derive(Resource, Default, Debug)]
+pub struct Autopilot;
+
+/// The current run is the attract mode of the title screen.
+/// It keeps what the title screen had prepared for the next run of the player.
+#[derive(Resource, Debug)]
+pub struct AttractMode {
+    /// The seed the player may have typed in.
+    title_seed: WorldSeed,
+    /// The replay of `--replay`, waiting for the first run.
+    replay_mode: ReplayMode,
+}
+
+/// Seconds the title screen has been left alone.
+#[derive(Resource, Default, Debug)]
+struct TitleIdle(f32);
+
+/// Replaces the input of the tick with the bot's.
+fn fly_ship(
+    ship: Single<(&Transform, &LinearVelocity), With<Player>>,
+    gas_tree: Res<KDTree2<GasOrb>>,
+    gas_orbs: Query<&GasOrb>,
+    red_orb_tree: Res<KDTree2<RedGasOrb>>,
+    asteroids: Query<(&Transform, &Asteroid, &LinearVelocity)>,
+    explosions: Query<&RedOrbExplosion>,
+    gravity_bodies: Query<&GravityBody>,
+    fire: Res<FireField>,
+    mut next: ResMut<NextTickInput>,
+) {
+    let (ship_tr, velocity) = ship.into_inner();
+    let pos = ship_tr.translation.truncate();
+    let forward = ship_tr.up().truncate();
+    let heading = velocity.0.normalize_or(forward);
+    // the circle in front of the ship
+    let ahead = pos + heading * LOOKAHEAD / 2.0;
+
+    let mut wanted = heading;
+    let mut dash = None;
+
+    // towards the gas ahead, the heavier and closer the better
+    let mut gas_dir = Vec2::ZERO;
+    let mut gas_weight = 0.0;
+    for (orb_pos, entity) in gas_tree.within_distance(ahead, LOOKAHEAD / 2.0) {
+        let Some(Ok(orb)) = entity.map(|e| gas_orbs.get(e)) else {
+            continue;
+        };
+        let offset = orb_pos - pos;
+        let distance = offset.length().max(1.0);
+        let weight = orb.0 / (1.0 + distance / 100.0);
+        gas_dir += offset / distance * weight;
+        gas_weight += weight;
+    }
+    if gas_weight > 0.0 {
+        wanted += gas_dir / gas_weight * GAS_PULL;
+    }
+
+    // away from where the asteroids on a collision course will be
+    for (asteroid_tr, asteroid, asteroid_velocity) in &asteroids {
+        let offset = asteroid_tr.translation.truncate() - pos;
+        let closing = velocity.0 - asteroid_velocity.0;
+        let Some(t) = closest_approach_secs(offset, closing) else {
+            continue;
+        };
+        if t > ASTEROID_LOOKAHEAD_SECS {
+            continue;
+        }
+
+        let miss = offset - closing * t;
+        let clearance = asteroid.radius * 0.85 + SHIP_RADIUS + ASTEROID_MARGIN;
+        if miss.length() >= clearance {
+            continue;
+        }
+
+        // dead ahead, either side will do
+        let away = (-miss).try_normalize().unwrap_or_else(|| heading.perp());
+        wanted += away * ASTEROID_PUSH * (1.0 - t / ASTEROID_LOOKAHEAD_SECS);
+
+        if t < DASH_SECS && miss.length() < clearance / 2.0 {
+            dash = Some(if away.dot(forward.perp()) > 0.0 {
+                Action::DashLeft
+            } else {
+                Action::DashRight
+            });
+        }
+    }
+
+    // around the red orbs in the path
+    for (orb_pos, _) in red_orb_tree.within_distance(ahead, LOOKAHEAD / 2.0) {
+        let offset = orb_pos - pos;
+        let along = offset.dot(heading);
+        let side = offset - heading * along;
+        let distance = side.length();
+        if along <= 0.0 || distance >= RED_ORB_CLEARANCE {
+            continue;
+        }
+        let away = (-side).try_normalize().unwrap_or_else(|| heading.perp());
+        wanted += away * RED_ORB_PUSH * (1.0 - distance / RED_ORB_CLEARANCE);
+    }
+
+    // away from the explosions closing in, harder once inside
+    for explosion in &explosions {
+        let offset = pos - explosion.pos;
+        let gap = offset.length() - explosion.radius;
+        if gap >= EXPLOSION_MARGIN {
+            continue;
+        }
+        let away = offset.try_normalize().unwrap_or(heading);
+        wanted += away * EXPLOSION_PUSH * (1.0 - gap / EXPLOSION_MARGIN).min(2.0);
+    }
+
+    // not into the fire
+    for distance in FIRE_PROBES {
+        for angle in [-0.5, 0.0, 0.5] {
+            let dir = Vec2::from_angle(angle).rotate(heading);
+            if fire.is_burning(pos + dir * distance) {
+                wanted -= dir * FIRE_PUSH;
+            }
+        }
+    }
+
+    // out of the pull of the black holes
+    for body in &gravity_bodies {
+        if body.kind != GravityKind::BlackHole {
+            continue;
+        }
+        let offset = pos - body.pos_2d();
+        let distance = offset.length();
+        if distance >= body.influence_radius {
+            continue;
+        }
+        wanted +=
+            offset / distance.max(1.0) * BLACK_HOLE_PUSH * (1.0 - distance / body.influence_radius);
+    }
+
+    let wanted = wanted.try_normalize().unwrap_or(heading);
+    // positive turns left, like the devices
+    let turn = (forward.angle_to(wanted) * TURN_GAIN).clamp(-1.0, 1.0);
+
+    next.0 = TickInput {
+        pressed: dash.into_iter().collect(),
+        turn,
+    };
+}
+
+/// Seconds until the object at the offset, closing in at the given velocity, is the closest.
+/// `None` if it's moving away.
+fn closest_approach_secs(offset: Vec2, closing: Vec2) -> Option<f32> {
+    let speed_sq = closing.length_squared();
+    if speed_sq < 1.0 {
+        return None;
+    }
+    let t = offset.dot(closing) / speed_sq;
+    (t >= 0.0).then_some(t)
+}
+
+fn reset_title_idle(mut idle: ResMut<TitleIdle>) {
+    idle.0 = 0.0;
+}
+
+/// Starts a run flown by the bot once the title screen was left alone long enough.
+fn start_attract_mode(
+    mut commands: Commands,
+    mut idle: ResMut<TitleIdle>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    gamepads: Query<&Gamepad>,
+    resource_handles: Res<ResourceHandles>,
+    mut seed: ResMut<WorldSeed>,
+    mut replay_mode: ResMut<ReplayMode>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    time: Res<Time>,
+) {
+    let touched = keyboard.get_pressed().next().is_some()
+        || mouse.get_pressed().next().is_some()
+        || mouse_motion.read().count() > 0
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.get_pressed().next().is_some());
+    if touched || !resource_handles.is_all_done() {
+        idle.0 = 0.0;
+        return;
+    }
+
+    idle.0 += time.delta_secs();
+    if idle.0 >= ATTRACT_IDLE_SECS {
+        // the run records in a world of its own, the player's setup waits for the title screen
+        commands.init_resource::<Autopilot>();
+        commands.insert_resource(AttractMode {
+            title_seed: std::mem::replace(&mut *seed, WorldSeed::random()),
+            replay_mode: std::mem::take(&mut *replay_mode),
+        });
+        next_screen.set(Screen::Gameplay);
+    }
+}
+
+fn spawn_attract_banner(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Attract Mode"),
+        DespawnOnExit(Screen::Gameplay),
+        children![widget::header("Demo"), widget::label("Press any key")],
+    ));
+}
+
+/// Any key or button brings the title screen back, so does the death of the ship.
+/// It runs after the fixed ticks of the frame, so it replaces the death screen the last tick may have set.
+fn stop_attract_mode(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    damage: Res<PlayerDamage>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let pressed = keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.get_just_pressed().next().is_some());
+    if pressed || damage.killed_by.is_some() {
+        next_screen.set(Screen::Title);
+    }
+}
+
+fn leave_attract_mode(mut commands: Commands) {
+    commands.remove_resource::<Autopilot>();
+}
+
+/// Gives the title screen back the seed and the replay it had before the attract mode.
+/// It runs after the seed is rerolled for the visit.
+fn restore_title(
+    mut commands: Commands,
+    mut attract: ResMut<AttractMode>,
+    mut seed: ResMut<WorldSeed>,
+    mut replay_mode: ResMut<ReplayMode>,
+) {
+    *seed = attract.title_seed;
+    *replay_mode = std::mem::take(&mut attract.replay_mode);
+    commands.remove_resource::<AttractMode>();
+}
+
+/// Arguments of `--autopilot`.
+struct AutopilotArgs {
+    seeds: u32,
+    first_seed: u32,
+    ticks: u32,
+}
+
+impl AutopilotArgs {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            seeds: 20,
+            first_seed: 0,
+            ticks: 7680,
+        };
+
+        let mut args = ModeArgs::new("--autopilot", args);
+        while let Some(option) = args.next_option() {
+            match option.as_str() {
+                "--seeds" => parsed.seeds = args.value(&option)?,
+                "--first-seed" => parsed.first_seed = args.value(&option)?,
+                "--ticks" => parsed.ticks = args.value(&option)?,
+                _ => return Err(unknown_option(&option)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Lets the bot fly a run on each of the seeds of `--autopilot` and prints their scores,
+/// then the spread of the scores. `args` is the command line without the program name.
+pub fn run(args: impl IntoIterator<Item = String>) -> AppExit {
+    let args = match AutopilotArgs::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("autopilot runs failed: {err}");
+            return AppExit::error();
+        }
+    };
+
+    let mut scores = vec![];
+    let mut deaths = 0;
+    for i in 0..args.seeds {
+        let seed = WorldSeed(args.first_seed.wrapping_add(i));
+        let mut simulation = Simulation::with_plugins(seed, |app: &mut App| {
+            app.init_resource::<Autopilot>();
+        });
+        while simulation.tick() < args.ticks && !simulation.is_dead() {
+            simulation.run_ticks(1);
+        }
+
+        let outcome = simulation
+            .killed_by()
+            .map_or("alive".to_string(), |source| {
+                format!("killed by {}", source.name())
+            });
+        println!(
+            "seed {}: score {:.1} after {} ticks, {outcome}",
+            seed.0,
+            simulation.score(),
+            simulation.tick(),
+        );
+
+        scores.push(simulation.score());
+        deaths += simulation.is_dead() as u32;
+    }
+
+    if scores.is_empty() {
+        return AppExit::Success;
+    }
+    scores.sort_by(f32::total_cmp);
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    println!(
+        "{} runs: score min {:.1}, median {:.1}, mean {mean:.1}, max {:.1}, {deaths} died",
+        scores.len(),
+        scores[0],
+        scores[scores.len() / 2],
+        scores[scores.len() - 1],
+    );
+
+    AppExit::Success
+}
@@ -0,0 +1,44 @@
+//! Command line of the modes running instead of the game, like `--headless` or `--gen-map`.
+//!
+//! A mode is selected by its own argument, the rest of the command line are options
+//! followed by their value, e.g. `--seed 42`.
+
+use std::str::FromStr;
+
+/// The options given to a mode, read one by one.
+pub struct ModeArgs<I> {
+    /// The argument selecting the mode, it's not an option.
+    mode: &'static str,
+    args: I,
+}
+
+impl<I: Iterator<Item = String>> ModeArgs<I> {
+    /// `args` is the command line without the program name.
+    pub fn new(mode: &'static str, args: impl IntoIterator<Item = String, IntoIter = I>) -> Self {
+        Self {
+            mode,
+            args: args.into_iter(),
+        }
+    }
+
+    /// The next option, `None` at the end of the command line.
+    pub fn next_option(&mut self) -> Option<String> {
+        self.args.find(|arg| arg != self.mode)
+    }
+
+    /// The value following the option.
+    pub fn value<T: FromStr>(&mut self, option: &str) -> Result<T, String> {
+        let value = self
+            .args
+            .next()
+            .ok_or(format!("missing value for `{option}`"))?;
+        value
+            .parse()
+            .map_err(|_| format!("invalid value `{value}` for `{option}`"))
+    }
+}
+
+/// The error for an option the mode doesn't have.
+pub fn unknown_option(option: &str) -> String {
+    format!("unknown argument `{option}`")
+}
@@ -4,7 +4,6 @@ use avian2d::prelude::PhysicsDebugPlugin;
 use bevy::{
     color::palettes::css::WHITE,
     input::common_conditions::{input_just_pressed, input_pressed},
-    post_process::bloom::Bloom,
     prelude::*,
 };
 
@@ -20,7 +19,7 @@ use iyes_perf_ui::{
     prelude::PerfUiRoot,
 };
 
-use crate::{screens::Screen, vfx};
+use crate::{screens::Screen, settings::Settings};
 
 const DEBUG_TOGGLE_KEY: KeyCode = KeyCode::Backquote;
 const PERFUI_TOGGLE_KEY: KeyCode = KeyCode::F3;
@@ -130,20 +129,7 @@ fn spawn_grid(mut commands: Commands, mut gizmo_assets: ResMut<Assets<GizmoAsset
     });
 }
 
-fn tooggle_bloom(
-    mut commands: Commands,
-    mut camera: Query<Entity, With<Camera>>,
-    mut state: Local<bool>,
-) {
-    let camera = camera.single_mut().unwrap();
-    let mut camera = commands.get_entity(camera).unwrap();
-
-    if *state {
-        info!("Enable Bloom");
-        camera.insert(vfx::BASE_BLOOM);
-    } else {
-        info!("Disable Bloom");
-        camera.remove::<Bloom>();
-    }
-    *state = !*state;
+fn tooggle_bloom(mut settings: ResMut<Settings>) {
+    settings.bloom = !settings.bloom;
+    info!("Bloom: {}", settings.bloom);
 }
@@ -0,0 +1,252 @@
+//! Local leaderboard and history of the runs.
+//!
+//! The stats of the current run are tracked in [`RunStats`], and recorded into [`HighScores`]
+//! when the player dies. Both tables are saved to [`HIGH_SCORES_FILE`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    PausableSystems, persistence,
+    player::{Player, Score},
+    replay::RunReplay,
+    screens::Screen,
+    space::WorldSeed,
+};
+
+/// Saved leaderboard, in [`persistence::save_dir`].
+pub const HIGH_SCORES_FILE: &str = "high_scores.ron";
+/// Runs kept on the leaderboard.
+pub const MAX_HIGH_SCORES: usize = 10;
+/// Latest runs kept in the history, whatever their score.
+pub const MAX_HISTORY: usize = 20;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(HighScores::load())
+        .init_resource::<RunStats>()
+        .add_systems(OnEnter(Screen::Gameplay), reset_run_stats)
+        .add_systems(OnEnter(Screen::Dead), record_run)
+        .add_systems(
+            Update,
+            track_run_stats
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
+}
+
+/// A finished run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub score: f32,
+    pub peak_aura: f32,
+    pub distance: f32,
+    /// Time played, pauses excluded.
+    pub duration_secs: f32,
+    pub seed: u32,
+    /// Seconds since the Unix epoch, 0 if unknown.
+    pub date: u64,
+}
+
+impl RunRecord {
+    /// `m:ss`
+    pub fn duration_text(&self) -> String {
+        let secs = self.duration_secs as u32;
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+
+    /// `YYYY-MM-DD` in UTC.
+    pub fn date_text(&self) -> String {
+        if self.date == 0 {
+            return "-".to_string();
+        }
+
+        // days to civil date, from Howard Hinnant's `civil_from_days`
+        let z = (self.date / 86_400) as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HighScores {
+    /// Best runs first.
+    pub top: Vec<RunRecord>,
+    /// Latest runs first.
+    pub history: Vec<RunRecord>,
+}
+
+impl HighScores {
+    /// The saved leaderboard, or an empty one.
+    pub fn load() -> Self {
+        persistence::load(HIGH_SCORES_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        persistence::save(HIGH_SCORES_FILE, self);
+    }
+
+    /// Adds the run to the history and to the leaderboard if it's good enough.
+    /// Returns its rank on the leaderboard (0 is the best).
+    pub fn record(&mut self, run: RunRecord) -> Option<usize> {
+        self.history.insert(0, run.clone());
+        self.history.truncate(MAX_HISTORY);
+
+        // ties go to the older run
+        let rank = self.top.partition_point(|other| other.score >= run.score);
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.top.insert(rank, run);
+        self.top.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// The run that just ended, and where it landed on the leaderboard.
+#[derive(Resource, Clone, Debug)]
+pub struct LatestRun {
+    pub record: RunRecord,
+    pub rank: Option<usize>,
+}
+
+/// Stats of the current run, besides the [`Score`].
+#[derive(Resource, Default, Debug)]
+pub struct RunStats {
+    pub peak_aura: f32,
+    pub distance: f32,
+    pub duration_secs: f32,
+    last_pos: Option<Vec2>,
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn track_run_stats(
+    mut stats: ResMut<RunStats>,
+    player: Query<(&Player, &Transform)>,
+    time: Res<Time>,
+) {
+    let Ok((player, tr)) = player.single() else {
+        return;
+    };
+    let pos = tr.translation.truncate();
+
+    stats.peak_aura = stats.peak_aura.max(player.aura_points);
+    stats.distance += stats.last_pos.map_or(0.0, |last| last.distance(pos));
+    stats.last_pos = Some(pos);
+    stats.duration_secs += time.delta_secs();
+}
+
+/// Records the run when the player dies, before the death menu shows it.
+/// Watching a replay doesn't get on the leaderboard a second time.
+pub fn record_run(
+    mut commands: Commands,
+    mut high_scores: ResMut<HighScores>,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    seed: Res<WorldSeed>,
+    run_replay: Res<RunReplay>,
+) {
+    let record = RunRecord {
+        score: score.0,
+        peak_aura: stats.peak_aura,
+        distance: stats.distance,
+        duration_secs: stats.duration_secs,
+        seed: seed.0,
+        date: now(),
+    };
+
+    if run_replay.is_playing() {
+        commands.insert_resource(LatestRun { record, rank: None });
+        return;
+    }
+
+    let rank = high_scores.record(record.clone());
+    high_scores.save();
+    commands.insert_resource(LatestRun { record, rank });
+}
+
+/// Seconds since the Unix epoch. The web has no system clock in `std`, there it is 0.
+fn now() -> u64 {
+    if cfg!(target_family = "wasm") {
+        return 0;
+    }
+
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A run told apart from the others by its seed.
+    fn run(score: f32, seed: u32) -> RunRecord {
+        RunRecord {
+            score,
+            peak_aura: 0.0,
+            distance: 0.0,
+            duration_secs: 0.0,
+            seed,
+            date: 0,
+        }
+    }
+
+    fn seeds(runs: &[RunRecord]) -> Vec<u32> {
+        runs.iter().map(|run| run.seed).collect()
+    }
+
+    #[test]
+    fn runs_are_ranked_by_score() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.record(run(5.0, 0)), Some(0));
+        assert_eq!(scores.record(run(9.0, 1)), Some(0));
+        assert_eq!(scores.record(run(7.0, 2)), Some(1));
+        // ties go to the older run
+        assert_eq!(scores.record(run(7.0, 3)), Some(2));
+
+        assert_eq!(seeds(&scores.top), [1, 2, 3, 0]);
+        assert_eq!(seeds(&scores.history), [3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn leaderboard_keeps_the_best_runs() {
+        let mut scores = HighScores::default();
+        for seed in 0..MAX_HIGH_SCORES as u32 {
+            scores.record(run(10.0 + seed as f32, seed));
+        }
+
+        // as good as the last one, but newer
+        assert_eq!(scores.record(run(10.0, 100)), None);
+        assert_eq!(scores.record(run(0.0, 101)), None);
+        assert_eq!(scores.top.len(), MAX_HIGH_SCORES);
+
+        // the worst run drops off the end
+        assert_eq!(scores.record(run(10.5, 102)), Some(MAX_HIGH_SCORES - 1));
+        assert_eq!(scores.top.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores.top.last().unwrap().seed, 102);
+        assert!(!seeds(&scores.top).contains(&0));
+    }
+
+    #[test]
+    fn history_keeps_the_latest_runs() {
+        let mut scores = HighScores::default();
+        for seed in 0..MAX_HISTORY as u32 + 5 {
+            scores.record(run(1.0, seed));
+        }
+
+        assert_eq!(scores.history.len(), MAX_HISTORY);
+        assert_eq!(scores.history[0].seed, MAX_HISTORY as u32 + 4);
+        assert_eq!(scores.history.last().unwrap().seed, 5);
+    }
+}
@@ -0,0 +1,518 @@
+//! Action-based input.
+//!
+//! Gameplay code reads the [`ActionState`] resource instead of the keyboard or the gamepads,
+//! so the controls can be remapped in [`InputBindings`] and played without any device.
+//! Code can press actions through [`InjectedActions`], e.g. a headless `App` without the `InputPlugin`
+//! can insert [`InjectedActions`] with the actions pressed before calling `app.update()`.
+//! The bindings are saved to [`BINDINGS_FILE`] when changed, and loaded at startup.
+//!
+//! The simulation in `FixedUpdate` reads [`TickActions`] instead, a copy of the actions
+//! advanced once per fixed tick, so a press is seen by exactly one tick whatever the frame rate,
+//! and replays can feed it recorded input instead of the devices.
+
+use std::collections::BTreeMap;
+
+use bevy::{input::InputSystems, platform::collections::HashSet, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{Pause, persistence, screens::Screen};
+
+/// Saved bindings, in [`persistence::save_dir`].
+pub const BINDINGS_FILE: &str = "bindings.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(InputBindings::load())
+        .init_resource::<InjectedActions>()
+        .init_resource::<ActionState>()
+        .init_resource::<NextTickInput>()
+        .init_resource::<TickActions>()
+        // the ticks of a pause don't simulate anything, a press during one lands on the next simulated tick
+        .configure_sets(
+            FixedFirst,
+            (
+                TickInputSystems::Sample,
+                TickInputSystems::Script,
+                TickInputSystems::Apply,
+            )
+                .chain()
+                .run_if(in_state(Pause(false))),
+        )
+        .add_systems(OnEnter(Screen::Gameplay), reset_tick_actions)
+        .add_systems(PreUpdate, update_action_state.after(InputSystems))
+        .add_systems(
+            FixedFirst,
+            (
+                sample_tick_input.in_set(TickInputSystems::Sample),
+                apply_tick_input.in_set(TickInputSystems::Apply),
+            ),
+        );
+}
+
+/// Preparation of the input of each fixed tick, in `FixedFirst`.
+/// Systems replacing the input of the devices, like replays, run between `Script` and `Apply`.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickInputSystems {
+    /// The actions currently held on the devices are written to [`NextTickInput`].
+    Sample,
+    /// Scripted controls, e.g. of a test scene, may overwrite [`NextTickInput`].
+    Script,
+    /// [`NextTickInput`] becomes the new [`TickActions`].
+    Apply,
+}
+
+/// Everything the player can do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// Digital half of the turn axis, see [`ActionState::turn`].
+    TurnLeft,
+    /// Digital half of the turn axis, see [`ActionState::turn`].
+    TurnRight,
+    Brake,
+    DashLeft,
+    DashRight,
+    BulletTime,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Brake,
+        Action::DashLeft,
+        Action::DashRight,
+        Action::BulletTime,
+        Action::Pause,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::TurnLeft => "Turn left",
+            Action::TurnRight => "Turn right",
+            Action::Brake => "Brake",
+            Action::DashLeft => "Dash left",
+            Action::DashRight => "Dash right",
+            Action::BulletTime => "Bullet time",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// A physical button an action can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A button of any connected gamepad.
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    /// Short human readable name, e.g. `A`, `Left` or `Pad South`.
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let short = ["Key", "Digit", "Arrow"]
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .filter(|rest| !rest.is_empty())
+                    .unwrap_or(name.as_str());
+                short.to_string()
+            }
+            Binding::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+
+    fn is_pressed(
+        self,
+        keyboard: Option<&ButtonInput<KeyCode>>,
+        gamepads: &Query<&Gamepad>,
+    ) -> bool {
+        match self {
+            Binding::Key(key) => keyboard.is_some_and(|keyboard| keyboard.pressed(key)),
+            Binding::Gamepad(button) => gamepads.iter().any(|gamepad| gamepad.pressed(button)),
+        }
+    }
+}
+
+/// Which buttons trigger which actions.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub buttons: BTreeMap<Action, Vec<Binding>>,
+    /// Gamepad axis turning the ship, pushing it right turns right.
+    pub turn_axis: GamepadAxis,
+}
+
+impl InputBindings {
+    /// The saved bindings, or the default ones if nothing was saved.
+    /// Actions missing from the file (e.g. added in a later version) get their default bindings.
+    pub fn load() -> Self {
+        let Some(mut bindings) = persistence::load::<Self>(BINDINGS_FILE) else {
+            return Self::default();
+        };
+
+        for (action, defaults) in Self::default().buttons {
+            bindings.buttons.entry(action).or_insert(defaults);
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        persistence::save(BINDINGS_FILE, self);
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.buttons.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The other action already using the binding, if any.
+    pub fn conflict(&self, action: Action, binding: Binding) -> Option<Action> {
+        self.buttons
+            .iter()
+            .find(|(other, bindings)| **other != action && bindings.contains(&binding))
+            .map(|(other, _)| *other)
+    }
+
+    /// Binds the button to one slot of the action, its other bindings are kept.
+    /// A slot past the last binding adds the button instead.
+    ///
+    /// Returns `false` when the button is already in another slot of the action,
+    /// it would only be listed twice, so nothing changes.
+    pub fn rebind(&mut self, action: Action, slot: usize, binding: Binding) -> bool {
+        let bindings = self.buttons.entry(action).or_default();
+        if let Some(bound) = bindings.iter().position(|b| *b == binding) {
+            return bound == slot;
+        }
+        match bindings.get_mut(slot) {
+            Some(old) => *old = binding,
+            None => bindings.push(binding),
+        }
+        true
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Binding::{Gamepad as Pad, Key};
+
+        let buttons = [
+            (
+                Action::TurnLeft,
+                vec![
+                    Key(KeyCode::KeyA),
+                    Key(KeyCode::ArrowLeft),
+                    Pad(GamepadButton::DPadLeft),
+                ],
+            ),
+            (
+                Action::TurnRight,
+                vec![
+                    Key(KeyCode::KeyD),
+                    Key(KeyCode::ArrowRight),
+                    Pad(GamepadButton::DPadRight),
+                ],
+            ),
+            (
+                Action::Brake,
+                vec![Key(KeyCode::KeyS), Pad(GamepadButton::LeftTrigger2)],
+            ),
+            (
+                Action::DashLeft,
+                vec![Key(KeyCode::KeyQ), Pad(GamepadButton::LeftTrigger)],
+            ),
+            (
+                Action::DashRight,
+                vec![Key(KeyCode::KeyE), Pad(GamepadButton::RightTrigger)],
+            ),
+            (
+                Action::BulletTime,
+                vec![Key(KeyCode::Space), Pad(GamepadButton::South)],
+            ),
+            (
+                Action::Pause,
+                vec![
+                    Key(KeyCode::KeyP),
+                    Key(KeyCode::Escape),
+                    Pad(GamepadButton::Start),
+                ],
+            ),
+        ];
+
+        Self {
+            buttons: buttons.into_iter().collect(),
+            turn_axis: GamepadAxis::LeftStickX,
+        }
+    }
+}
+
+/// Actions pressed by code rather than by a device, they are merged with the devices every frame.
+/// Used by tests and anything else driving the ship.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct InjectedActions {
+    pub pressed: HashSet<Action>,
+    /// Overrides the turn axis of the devices when set.
+    pub turn: Option<f32>,
+}
+
+impl InjectedActions {
+    pub fn press(&mut self, action: Action) {
+        self.pressed.insert(action);
+    }
+
+    pub fn release(&mut self, action: Action) {
+        self.pressed.remove(&action);
+    }
+
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+        self.turn = None;
+    }
+}
+
+/// State of the actions this frame.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    turn: f32,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Whether the action got pressed this frame.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// From -1.0 (full right) to 1.0 (full left), analog on gamepads.
+    pub fn turn(&self) -> f32 {
+        self.turn
+    }
+
+    /// Replaces the state with the next frame, the actions that weren't pressed before become just pressed.
+    pub fn advance(&mut self, pressed: HashSet<Action>, turn: f32) {
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.pressed = pressed;
+        self.turn = turn.clamp(-1.0, 1.0);
+    }
+
+    /// The actions held right now, without the edges.
+    pub fn held(&self) -> TickInput {
+        let mut pressed: Vec<_> = self.pressed.iter().copied().collect();
+        pressed.sort();
+        TickInput {
+            pressed,
+            turn: self.turn,
+        }
+    }
+}
+
+/// Actions held during one fixed tick, the unit of input of replays.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickInput {
+    /// Sorted, so equal inputs compare equal.
+    pub pressed: Vec<Action>,
+    pub turn: f32,
+}
+
+/// Input of the coming fixed tick. Sampled from the devices, replays overwrite it.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct NextTickInput(pub TickInput);
+
+/// State of the actions during the current fixed tick, read by the simulation in `FixedUpdate`.
+/// Just pressed means pressed this tick, rather than this frame.
+#[derive(Resource, Default, Clone, Debug, Deref)]
+pub struct TickActions(ActionState);
+
+/// Run condition that is active on the fixed tick the action got pressed.
+pub fn tick_action_just_pressed(action: Action) -> impl FnMut(Res<TickActions>) -> bool + Clone {
+    move |actions: Res<TickActions>| actions.just_pressed(action)
+}
+
+/// Every run starts with nothing pressed, so the first tick sees the same edges in a replay.
+fn reset_tick_actions(mut actions: ResMut<TickActions>) {
+    *actions = TickActions::default();
+}
+
+fn sample_tick_input(actions: Res<ActionState>, mut next: ResMut<NextTickInput>) {
+    next.0 = actions.held();
+}
+
+fn apply_tick_input(next: Res<NextTickInput>, mut actions: ResMut<TickActions>) {
+    let pressed = next.0.pressed.iter().copied().collect();
+    actions.0.advance(pressed, next.0.turn);
+}
+
+/// Run condition that is active on the frame the action got pressed.
+pub fn action_just_pressed(action: Action) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |actions: Res<ActionState>| actions.just_pressed(action)
+}
+
+fn update_action_state(
+    bindings: Res<InputBindings>,
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    gamepads: Query<&Gamepad>,
+    injected: Res<InjectedActions>,
+    mut actions: ResMut<ActionState>,
+) {
+    let mut pressed = injected.pressed.clone();
+    for action in Action::ALL {
+        let is_pressed = bindings
+            .get(action)
+            .iter()
+            .any(|binding| binding.is_pressed(keyboard.as_deref(), &gamepads));
+        if is_pressed {
+            pressed.insert(action);
+        }
+    }
+
+    let digital_turn =
+        pressed.contains(&Action::TurnLeft) as i32 - pressed.contains(&Action::TurnRight) as i32;
+    // the strongest stick wins, the gamepad settings already apply a deadzone
+    let analog_turn = gamepads
+        .iter()
+        .filter_map(|gamepad| gamepad.get(bindings.turn_axis))
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0);
+
+    let turn = injected.turn.unwrap_or(digital_turn as f32 - analog_turn);
+    actions.advance(pressed, turn);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use avian2d::prelude::{Physics, PhysicsTime};
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::player::{
+        Player,
+        abilities::{BULLET_TIME_AURA_COST, BULLET_TIME_SPEED, go_into_bullet_time},
+        movement::AuraEarned,
+    };
+
+    /// Bare app with only the input layer and bullet time, no devices and no physics.
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                10,
+            )))
+            .init_state::<Pause>()
+            .init_state::<Screen>()
+            .init_resource::<Time<Physics>>()
+            .add_message::<AuraEarned>()
+            .add_plugins(plugin)
+            .insert_resource(InputBindings::default())
+            .add_systems(
+                FixedUpdate,
+                go_into_bullet_time.run_if(tick_action_just_pressed(Action::BulletTime)),
+            );
+        app.world_mut().spawn(Player {
+            aura_points: 2.0 * BULLET_TIME_AURA_COST,
+            ..default()
+        });
+        app
+    }
+
+    fn aura(app: &mut App) -> f32 {
+        let mut players = app.world_mut().query::<&Player>();
+        players.single(app.world()).unwrap().aura_points
+    }
+
+    #[test]
+    fn scripted_tick_input_drives_the_ship() {
+        let mut app = app();
+        // held for the whole run, like a replay would
+        app.add_systems(
+            FixedFirst,
+            (|mut next: ResMut<NextTickInput>| {
+                next.0 = TickInput {
+                    pressed: vec![Action::BulletTime],
+                    turn: 0.0,
+                };
+            })
+            .in_set(TickInputSystems::Script),
+        );
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let actions = app.world().resource::<TickActions>();
+        assert!(actions.pressed(Action::BulletTime));
+        assert!(!actions.just_pressed(Action::BulletTime));
+        let physics_speed = app.world().resource::<Time<Physics>>().relative_speed();
+        assert_eq!(physics_speed, BULLET_TIME_SPEED);
+        // held down, it only triggered once
+        assert_eq!(aura(&mut app), BULLET_TIME_AURA_COST);
+    }
+
+    #[test]
+    fn device_input_is_overwritten_by_the_script() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<InjectedActions>()
+            .press(Action::BulletTime);
+        app.add_systems(
+            FixedFirst,
+            (|mut next: ResMut<NextTickInput>| next.0 = TickInput::default())
+                .in_set(TickInputSystems::Script),
+        );
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert!(
+            app.world()
+                .resource::<ActionState>()
+                .pressed(Action::BulletTime)
+        );
+        assert!(
+            !app.world()
+                .resource::<TickActions>()
+                .pressed(Action::BulletTime)
+        );
+        assert_eq!(aura(&mut app), 2.0 * BULLET_TIME_AURA_COST);
+    }
+
+    #[test]
+    fn rebinding_a_slot_keeps_the_others() {
+        let mut bindings = InputBindings::default();
+        assert!(bindings.rebind(Action::Pause, 0, Binding::Key(KeyCode::KeyO)));
+
+        assert_eq!(
+            bindings.get(Action::Pause),
+            [
+                Binding::Key(KeyCode::KeyO),
+                Binding::Key(KeyCode::Escape),
+                Binding::Gamepad(GamepadButton::Start),
+            ]
+        );
+    }
+
+    #[test]
+    fn rebinding_past_the_last_slot_adds_a_binding() {
+        let mut bindings = InputBindings::default();
+        assert!(bindings.rebind(Action::Brake, 5, Binding::Key(KeyCode::ArrowDown)));
+        // bound twice would only waste a slot
+        assert!(!bindings.rebind(Action::Brake, 0, Binding::Key(KeyCode::ArrowDown)));
+        // binding a slot to what it has already is fine
+        assert!(bindings.rebind(Action::Brake, 2, Binding::Key(KeyCode::ArrowDown)));
+
+        assert_eq!(
+            bindings.get(Action::Brake),
+            [
+                Binding::Key(KeyCode::KeyS),
+                Binding::Gamepad(GamepadButton::LeftTrigger2),
+                Binding::Key(KeyCode::ArrowDown),
+            ]
+        );
+    }
+}
@@ -5,12 +5,20 @@
 mod asset_tracking;
 mod asteroids;
 mod audio;
+mod autopilot;
+mod cli;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod high_scores;
+mod input;
 mod menus;
+mod persistence;
 mod player;
 mod red_gas;
+mod replay;
 mod screens;
+mod settings;
+mod simulation;
 mod space;
 mod speed_tracers;
 mod test_scenes;
@@ -19,7 +27,6 @@ mod tweening;
 mod utils;
 mod vfx;
 
-use avian2d::prelude::*;
 use bevy::log::Level;
 use bevy::{
     asset::AssetMetaCheck,
@@ -40,6 +47,23 @@ use bevy_mod_debugdump::schedule_graph::Settings;
 use tweening::TweeningPlugin;
 
 fn main() -> AppExit {
+    // Export a map of the generated world instead of running the game.
+    if std::env::args().any(|arg| arg == "--gen-map") {
+        return space::map_export::run(std::env::args().skip(1));
+    }
+    // Simulate a run without window, renderer or audio.
+    if std::env::args().any(|arg| arg == "--headless") {
+        return simulation::run(std::env::args().skip(1));
+    }
+    // Play the test scenes headlessly and report how they went.
+    if std::env::args().any(|arg| arg == "--test-scenes") {
+        return test_scenes::run(std::env::args().skip(1));
+    }
+    // Let the autopilot play a run on each seed and report the scores.
+    if std::env::args().any(|arg| arg == "--autopilot") {
+        return autopilot::run(std::env::args().skip(1));
+    }
+
     App::new().add_plugins(AppPlugin).run()
 }
 
@@ -80,11 +104,10 @@ impl Plugin for AppPlugin {
                     filter: "avian2d::dynamics::solver::islands::sleeping=error".to_string(),
                     ..default()
                 }),
-            PhysicsPlugins::default().with_length_unit(1.0),
-            #[cfg(feature = "framepace")]
             FramepacePlugin,
             TweeningPlugin,
             AudioPlugin,
+            FrameTimeDiagnosticsPlugin::default(),
         ));
 
         // Add other plugins.
@@ -93,20 +116,20 @@ impl Plugin for AppPlugin {
             audio::plugin,
             #[cfg(feature = "dev")]
             dev_tools::plugin,
-            asteroids::plugin,
+            high_scores::plugin,
+            simulation::plugin,
             menus::plugin,
             screens::plugin,
+            settings::plugin,
             theme::plugin,
-            player::plugin,
-            space::plugin,
-            red_gas::plugin,
+            replay::plugin,
             // utils::plugin,
             speed_tracers::plugin,
-            FrameTimeDiagnosticsPlugin::default(),
+            #[cfg(feature = "dev")]
+            test_scenes::plugin,
         ));
 
-        app.insert_resource(ClearColor(Color::srgb(0.12, 0.1, 0.14)))
-            .insert_resource(Gravity(Vec2::ZERO));
+        app.insert_resource(ClearColor(Color::srgb(0.12, 0.1, 0.14)));
 
         // Order new `AppSystems` variants by adding them here:
         app.configure_sets(
@@ -119,11 +142,6 @@ impl Plugin for AppPlugin {
                 .chain(),
         );
 
-        // Set up the `Pause` state.
-        app.init_state::<Pause>();
-        app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
-        app.configure_sets(FixedUpdate, PausableSystems.run_if(in_state(Pause(false))));
-
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
 
@@ -169,6 +187,7 @@ fn spawn_camera(mut commands: Commands) {
         },
         Camera3d::default(),
         vfx::BASE_BLOOM,
+        audio::spatial::AudioListener,
         Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Dir3::Y),
     ));
 
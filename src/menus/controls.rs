@@ -0,0 +1,254 @@
+//! The controls menu, where the player can rebind every action.
+
+use bevy::{
+    ecs::spawn::{Spawn, SpawnIter},
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    input::{Action, Binding, InputBindings},
+    menus::{Menu, previous_menu},
+    screens::Screen,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>()
+        .add_systems(
+            OnEnter(Menu::Controls),
+            (reset_rebinding, spawn_controls_menu),
+        )
+        .add_systems(
+            Update,
+            (
+                // Escape cancels the capture instead while a new binding is being captured
+                go_back.run_if(input_just_pressed(KeyCode::Escape).and(not(is_capturing))),
+                capture_binding.run_if(is_capturing),
+                update_controls_labels,
+            )
+                .chain()
+                .run_if(in_state(Menu::Controls)),
+        );
+}
+
+/// Bindings shown for each action, the defaults use up to three.
+const BINDING_SLOTS: usize = 3;
+
+/// Button showing one binding of an action, clicking it captures a replacement.
+/// An empty slot adds a binding.
+#[derive(Component)]
+struct BindingSlot {
+    action: Action,
+    slot: usize,
+}
+
+#[derive(Component)]
+struct ControlsStatusLabel;
+
+/// The binding slot waiting for a new button, and feedback for the player.
+#[derive(Resource, Default)]
+struct Rebinding {
+    action: Option<Action>,
+    slot: usize,
+    message: String,
+}
+
+fn is_capturing(rebinding: Res<Rebinding>) -> bool {
+    rebinding.action.is_some()
+}
+
+fn reset_rebinding(mut rebinding: ResMut<Rebinding>) {
+    *rebinding = Rebinding::default();
+}
+
+fn spawn_controls_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Controls Menu"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Controls),
+        children![
+            widget::header("Controls"),
+            controls_list(),
+            (widget::label(""), ControlsStatusLabel),
+            (
+                Name::new("Controls Buttons"),
+                Node {
+                    column_gap: Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button_medium("Defaults", reset_to_defaults),
+                    widget::button_medium("Back", go_back_on_click),
+                ],
+            ),
+        ],
+    ));
+}
+
+fn controls_list() -> impl Bundle {
+    (
+        Name::new("Controls List"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(Action::ALL.into_iter().map(control_row))),
+    )
+}
+
+fn control_row(action: Action) -> impl Bundle {
+    (
+        Name::new(format!("{} Row", action.name())),
+        Node {
+            column_gap: Px(30.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Children::spawn((
+            Spawn((
+                widget::label(action.name()),
+                Node {
+                    width: Px(200.0),
+                    ..default()
+                },
+            )),
+            SpawnIter((0..BINDING_SLOTS).map(move |slot| binding_slot(action, slot))),
+        )),
+    )
+}
+
+fn binding_slot(action: Action, slot: usize) -> impl Bundle {
+    (
+        widget::button_medium(
+            "",
+            move |_: On<Pointer<Click>>, mut rebinding: ResMut<Rebinding>| {
+                *rebinding = Rebinding {
+                    action: Some(action),
+                    slot,
+                    message: format!(
+                        "Press a key or a gamepad button for {} (Escape to cancel)",
+                        action.name()
+                    ),
+                };
+            },
+        ),
+        BindingSlot { action, slot },
+    )
+}
+
+/// Waits for a key or gamepad button, and binds it unless another action already uses it.
+fn capture_binding(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+
+    if keyboard.just_pressed(KeyCode::Escape) {
+        *rebinding = Rebinding::default();
+        return;
+    }
+
+    let pressed = keyboard
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+                .map(|button| Binding::Gamepad(*button))
+        });
+    let Some(binding) = pressed else {
+        return;
+    };
+
+    if let Some(other) = bindings.conflict(action, binding) {
+        rebinding.message = format!(
+            "{} is already used by {}, press another one",
+            binding.name(),
+            other.name()
+        );
+        return;
+    }
+
+    if !bindings.rebind(action, rebinding.slot, binding) {
+        rebinding.message = format!(
+            "{} is already bound to {}, press another one",
+            binding.name(),
+            action.name()
+        );
+        return;
+    }
+    bindings.save();
+    *rebinding = Rebinding {
+        action: None,
+        slot: 0,
+        message: format!("{} bound to {}", action.name(), binding.name()),
+    };
+}
+
+fn update_controls_labels(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    slots: Query<(Entity, &BindingSlot)>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text, Without<ControlsStatusLabel>>,
+    mut status: Single<&mut Text, With<ControlsStatusLabel>>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (entity, slot) in &slots {
+        let name = if rebinding.action == Some(slot.action) && rebinding.slot == slot.slot {
+            "...".to_string()
+        } else {
+            bindings
+                .get(slot.action)
+                .get(slot.slot)
+                .map(|binding| binding.name())
+                .unwrap_or_else(|| "+".to_string())
+        };
+        // the text is nested in the button
+        for descendant in children.iter_descendants(entity) {
+            if let Ok(mut text) = texts.get_mut(descendant) {
+                text.0.clone_from(&name);
+            }
+        }
+    }
+
+    status.0 = rebinding.message.clone();
+}
+
+fn reset_to_defaults(
+    _: On<Pointer<Click>>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    *bindings = InputBindings::default();
+    bindings.save();
+    *rebinding = Rebinding {
+        action: None,
+        slot: 0,
+        message: "Default controls restored".to_string(),
+    };
+}
+
+fn go_back_on_click(
+    _: On<Pointer<Click>>,
+    screen: Res<State<Screen>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    next_menu.set(previous_menu(screen.get()));
+}
+
+fn go_back(screen: Res<State<Screen>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(previous_menu(screen.get()));
+}
@@ -1,32 +1,78 @@
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::{AudioChannel, AudioControl};
 
 use crate::{
-    audio::AudioAssets,
-    player::{Player, Score},
+    audio::{AudioAssets, UiChannel, linear_to_decibels},
+    high_scores::{self, HighScores, LatestRun},
+    menus::high_scores::runs_table,
+    player::{Player, Score, death::PlayerDamage},
+    replay::{ReplayMode, RunReplay},
     screens::Screen,
+    settings::Settings,
+    space::WorldSeed,
     theme::widget,
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Dead), spawn_death_menu);
+    app.add_systems(
+        OnEnter(Screen::Dead),
+        spawn_death_menu.after(high_scores::record_run),
+    );
 }
 
+/// Best runs shown under the score.
+const HIGH_SCORES_SHOWN: usize = 5;
+
 fn spawn_death_menu(
     mut commands: Commands,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<UiChannel>>,
     audio_assets: Res<AudioAssets>,
+    settings: Res<Settings>,
     score: Res<Score>,
+    seed: Res<WorldSeed>,
+    damage: Res<PlayerDamage>,
+    high_scores: Res<HighScores>,
+    latest_run: Option<Res<LatestRun>>,
 ) {
-    audio.play(audio_assets.lose.clone()).with_volume(0.7);
+    let cause = damage.killed_by.unwrap_or_default();
+    let rank = latest_run.and_then(|run| run.rank);
+    let rank_text = match rank {
+        Some(0) => "New high score!".to_string(),
+        Some(rank) => format!("#{} on the leaderboard", rank + 1),
+        None => String::new(),
+    };
+    let mut top: Vec<_> = high_scores
+        .top
+        .iter()
+        .take(HIGH_SCORES_SHOWN)
+        .cloned()
+        .enumerate()
+        .collect();
+    // a rank below the shown ones gets its own line at the bottom
+    if let Some(rank) = rank
+        && rank >= HIGH_SCORES_SHOWN
+        && let Some(run) = high_scores.top.get(rank)
+    {
+        top.push((rank, run.clone()));
+    }
+
+    audio
+        .play(audio_assets.lose.clone())
+        .with_volume(linear_to_decibels(0.7 * settings.sfx_gain()));
     commands.spawn((
         widget::ui_root("DEAD"),
         // GlobalZIndex(1),
         DespawnOnExit(Screen::Dead),
         children![
             widget::header("Burned out..."),
+            widget::label(format!("Killed by {}", cause.name())),
             widget::label(format!("Score: {:.1}", score.0)),
+            widget::label(format!("Seed: {}", seed.0)),
+            widget::highlighted_label(rank_text),
+            runs_table(top, rank),
             widget::button("Restart", restart),
+            widget::button("New seed", restart_with_new_seed),
+            widget::button("Watch replay", watch_replay),
             widget::button("Quit to title", quit_to_title),
         ],
     ));
@@ -36,6 +82,26 @@ fn restart(_: On<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Gameplay);
 }
 
+fn restart_with_new_seed(
+    _: On<Pointer<Click>>,
+    mut seed: ResMut<WorldSeed>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    *seed = WorldSeed::random();
+    next_screen.set(Screen::Gameplay);
+}
+
+/// Plays the run that just ended back, or the one it was a replay of.
+fn watch_replay(
+    _: On<Pointer<Click>>,
+    run_replay: Res<RunReplay>,
+    mut mode: ResMut<ReplayMode>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    *mode = ReplayMode::Play(run_replay.replay.clone());
+    next_screen.set(Screen::Gameplay);
+}
+
 fn quit_to_title(_: On<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -0,0 +1,107 @@
+//! The high scores menu, with the leaderboard and the latest runs.
+
+use bevy::{
+    ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    high_scores::{HighScores, RunRecord},
+    menus::Menu,
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::HighScores), spawn_high_scores_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::HighScores).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// Latest runs shown below the leaderboard.
+const HISTORY_SHOWN: usize = 5;
+
+fn spawn_high_scores_menu(mut commands: Commands, high_scores: Res<HighScores>) {
+    let top = high_scores.top.iter().cloned().enumerate().collect();
+    let history = high_scores
+        .history
+        .iter()
+        .take(HISTORY_SHOWN)
+        .cloned()
+        .enumerate()
+        .collect();
+
+    commands.spawn((
+        widget::ui_root("High Scores Menu"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::HighScores),
+        children![
+            widget::header("High Scores"),
+            runs_table(top, None),
+            widget::header("Latest Runs"),
+            runs_table(history, None),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+/// A table of runs, one per line, each with its rank (0 is the first line of a full table).
+/// The highlighted rank stands out, e.g. the run that just ended.
+pub(super) fn runs_table(runs: Vec<(usize, RunRecord)>, highlighted: Option<usize>) -> impl Bundle {
+    const COLUMNS: [&str; 7] = [
+        "#",
+        "Score",
+        "Peak aura",
+        "Distance",
+        "Time",
+        "Seed",
+        "Date",
+    ];
+
+    (
+        Name::new("Runs Table"),
+        Node {
+            display: Display::Grid,
+            column_gap: Px(24.0),
+            row_gap: Px(4.0),
+            grid_template_columns: RepeatedGridTrack::auto(COLUMNS.len() as u16),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            if runs.is_empty() {
+                parent.spawn(widget::label("No runs yet"));
+                return;
+            }
+
+            for column in COLUMNS {
+                parent.spawn(widget::label(column));
+            }
+            for (rank, run) in &runs {
+                let cells = [
+                    format!("{}", rank + 1),
+                    format!("{:.1}", run.score),
+                    format!("{:.0}", run.peak_aura),
+                    format!("{:.0}", run.distance),
+                    run.duration_text(),
+                    run.seed.to_string(),
+                    run.date_text(),
+                ];
+                for cell in cells {
+                    if highlighted == Some(*rank) {
+                        parent.spawn(widget::highlighted_label(cell));
+                    } else {
+                        parent.spawn(widget::label(cell));
+                    }
+                }
+            }
+        })),
+    )
+}
+
+fn go_back_on_click(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
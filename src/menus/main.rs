@@ -2,12 +2,23 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::ResourceHandles, menus::Menu, screens::Screen, theme::widget};
+use crate::{
+    asset_tracking::ResourceHandles, menus::Menu, screens::Screen, space::WorldSeed, theme::widget,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
+    app.add_systems(
+        Update,
+        (edit_seed, update_seed_label)
+            .chain()
+            .run_if(in_state(Menu::Main)),
+    );
 }
 
+#[derive(Component)]
+struct SeedLabel;
+
 fn spawn_main_menu(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Main Menu"),
@@ -16,14 +27,22 @@ fn spawn_main_menu(mut commands: Commands) {
         #[cfg(not(target_family = "wasm"))]
         children![
             widget::button("Play", enter_loading_or_gameplay_screen),
-            // widget::button("Settings", open_settings_menu),
+            (widget::label(""), SeedLabel),
+            widget::button("Random seed", randomize_seed),
+            widget::button("Settings", open_settings_menu),
+            widget::button("Controls", open_controls_menu),
+            widget::button("High Scores", open_high_scores_menu),
             // widget::button("Credits", open_credits_menu),
             widget::button("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
         children![
             widget::button("Play", enter_loading_or_gameplay_screen),
-            // widget::button("Settings", open_settings_menu),
+            (widget::label(""), SeedLabel),
+            widget::button("Random seed", randomize_seed),
+            widget::button("Settings", open_settings_menu),
+            widget::button("Controls", open_controls_menu),
+            widget::button("High Scores", open_high_scores_menu),
             // widget::button("Credits", open_credits_menu),
         ],
     ));
@@ -41,10 +60,68 @@ fn enter_loading_or_gameplay_screen(
     }
 }
 
+fn randomize_seed(_: On<Pointer<Click>>, mut seed: ResMut<WorldSeed>) {
+    *seed = WorldSeed::random();
+}
+
+/// Lets the player type in a seed shared by someone else.
+///
+/// The first key typed over a seed the player didn't type starts a new one, a random seed
+/// is usually too long to append any digit to.
+fn edit_seed(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut seed: ResMut<WorldSeed>,
+    mut typed: Local<Option<u32>>,
+) {
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+
+    for key in keyboard_input.get_just_pressed() {
+        let digit = DIGITS.iter().position(|d| d == key);
+        if digit.is_none() && *key != KeyCode::Backspace {
+            continue;
+        }
+
+        let current = if *typed == Some(seed.0) { seed.0 } else { 0 };
+        let value = match digit {
+            // ignore digits that would overflow the seed
+            Some(digit) => current
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(digit as u32))
+                .unwrap_or(current),
+            None => current / 10,
+        };
+        seed.0 = value;
+        *typed = Some(value);
+    }
+}
+
+fn update_seed_label(seed: Res<WorldSeed>, mut label: Single<&mut Text, With<SeedLabel>>) {
+    label.0 = format!("Seed: {} (type to edit)", seed.0);
+}
+
 fn open_settings_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
 
+fn open_controls_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
+}
+
+fn open_high_scores_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::HighScores);
+}
+
 fn open_credits_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Credits);
 }
@@ -1,21 +1,27 @@
 #![allow(dead_code)]
 //! The game's menus and transitions between them.
 
+mod controls;
 mod credits;
 mod death;
+mod high_scores;
 mod main;
 mod pause;
 mod settings;
 
 use bevy::prelude::*;
 
+use crate::screens::Screen;
+
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
         // credits::plugin,
         main::plugin,
-        // settings::plugin,
+        controls::plugin,
+        high_scores::plugin,
+        settings::plugin,
         pause::plugin,
         death::plugin,
     ));
@@ -29,5 +35,16 @@ pub enum Menu {
     Main,
     Credits,
     Settings,
+    Controls,
+    HighScores,
     Pause,
 }
+
+/// The menu the menus opened from both the title screen and the pause menu go back to.
+fn previous_menu(screen: &Screen) -> Menu {
+    if screen == &Screen::Title {
+        Menu::Main
+    } else {
+        Menu::Pause
+    }
+}
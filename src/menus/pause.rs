@@ -20,7 +20,8 @@ fn spawn_pause_menu(mut commands: Commands) {
         children![
             widget::header("Game paused"),
             widget::button("Continue", close_menu),
-            // widget::button("Settings", open_settings_menu),
+            widget::button("Settings", open_settings_menu),
+            widget::button("Controls", open_controls_menu),
             widget::button("Quit to title", quit_to_title),
         ],
     ));
@@ -30,6 +31,10 @@ fn open_settings_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Men
     next_menu.set(Menu::Settings);
 }
 
+fn open_controls_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Controls);
+}
+
 fn close_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::None);
 }
@@ -2,24 +2,108 @@
 //!
 //! Additional settings and accessibility options should go here.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{
+    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{
+    menus::{Menu, previous_menu},
+    screens::Screen,
+    settings::Settings,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
+    app.add_systems(OnExit(Menu::Settings), save_settings);
     app.add_systems(
         Update,
         go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
     );
 
-    app.register_type::<GlobalVolumeLabel>();
+    app.register_type::<SettingLabel>();
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        update_setting_labels.run_if(in_state(Menu::Settings)),
     );
 }
 
+/// A line of the settings menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+enum SettingKind {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Vsync,
+    Framepace,
+    Bloom,
+    ScreenShake,
+    Ghost,
+}
+
+const MAX_SCREEN_SHAKE: f32 = 2.0;
+
+impl SettingKind {
+    const ALL: [SettingKind; 8] = [
+        SettingKind::MasterVolume,
+        SettingKind::MusicVolume,
+        SettingKind::SfxVolume,
+        SettingKind::Vsync,
+        SettingKind::Framepace,
+        SettingKind::Bloom,
+        SettingKind::ScreenShake,
+        SettingKind::Ghost,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SettingKind::MasterVolume => "Master Volume",
+            SettingKind::MusicVolume => "Music Volume",
+            SettingKind::SfxVolume => "Effects Volume",
+            SettingKind::Vsync => "VSync",
+            SettingKind::Framepace => "Frame Pacing",
+            SettingKind::Bloom => "Bloom",
+            SettingKind::ScreenShake => "Screen Shake",
+            SettingKind::Ghost => "Ghost Ship",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        let percent = |value: f32| format!("{:3.0}%", 100.0 * value);
+        let on_off = |value: bool| if value { "On" } else { "Off" }.to_string();
+
+        match self {
+            SettingKind::MasterVolume => percent(settings.master_volume),
+            SettingKind::MusicVolume => percent(settings.music_volume),
+            SettingKind::SfxVolume => percent(settings.sfx_volume),
+            SettingKind::Vsync => on_off(settings.vsync),
+            SettingKind::Framepace => on_off(settings.framepace),
+            SettingKind::Bloom => on_off(settings.bloom),
+            SettingKind::ScreenShake => percent(settings.screen_shake),
+            SettingKind::Ghost => on_off(settings.ghost),
+        }
+    }
+
+    /// Lowers (`direction < 0`) or raises the setting. Toggles flip either way.
+    fn step(self, settings: &mut Settings, direction: f32) {
+        let volume = |value: &mut f32| *value = (*value + 0.1 * direction).clamp(0.0, 1.0);
+
+        match self {
+            SettingKind::MasterVolume => volume(&mut settings.master_volume),
+            SettingKind::MusicVolume => volume(&mut settings.music_volume),
+            SettingKind::SfxVolume => volume(&mut settings.sfx_volume),
+            SettingKind::Vsync => settings.vsync = !settings.vsync,
+            SettingKind::Framepace => settings.framepace = !settings.framepace,
+            SettingKind::Bloom => settings.bloom = !settings.bloom,
+            SettingKind::ScreenShake => {
+                settings.screen_shake =
+                    (settings.screen_shake + 0.25 * direction).clamp(0.0, MAX_SCREEN_SHAKE);
+            }
+            SettingKind::Ghost => settings.ghost = !settings.ghost,
+        }
+    }
+}
+
 fn spawn_settings_menu(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Settings Menu"),
@@ -27,81 +111,92 @@ fn spawn_settings_menu(mut commands: Commands) {
         DespawnOnExit(Menu::Settings),
         children![
             widget::header("Settings"),
-            settings_grid(),
+            settings_list(),
             widget::button("Back", go_back_on_click),
         ],
     ));
 }
 
-fn settings_grid() -> impl Bundle {
+fn settings_list() -> impl Bundle {
     (
-        Name::new("Settings Grid"),
+        Name::new("Settings List"),
         Node {
-            display: Display::Grid,
+            flex_direction: FlexDirection::Column,
             row_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(SettingKind::ALL.into_iter().map(setting_row))),
+    )
+}
+
+fn setting_row(setting: SettingKind) -> impl Bundle {
+    (
+        Name::new(format!("{} Row", setting.name())),
+        Node {
+            display: Display::Grid,
             column_gap: Px(30.0),
             grid_template_columns: RepeatedGridTrack::px(2, 400.0),
             ..default()
         },
         children![
             (
-                widget::label("Master Volume"),
+                widget::label(setting.name()),
                 Node {
                     justify_self: JustifySelf::End,
                     ..default()
                 }
             ),
-            global_volume_widget(),
+            setting_widget(setting),
         ],
     )
 }
 
-fn global_volume_widget() -> impl Bundle {
+fn setting_widget(setting: SettingKind) -> impl Bundle {
     (
-        Name::new("Global Volume Widget"),
+        Name::new(format!("{} Widget", setting.name())),
         Node {
             justify_self: JustifySelf::Start,
             ..default()
         },
         children![
-            widget::button_small("-", lower_global_volume),
+            widget::button_small(
+                "-",
+                move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    setting.step(&mut settings, -1.0);
+                }
+            ),
             (
-                Name::new("Current Volume"),
+                Name::new("Current Value"),
                 Node {
+                    width: Px(100.0),
                     padding: UiRect::horizontal(Px(10.0)),
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
-                children![(widget::label(""), GlobalVolumeLabel)],
+                children![(widget::label(""), SettingLabel(setting))],
+            ),
+            widget::button_small(
+                "+",
+                move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    setting.step(&mut settings, 1.0);
+                }
             ),
-            widget::button_small("+", raise_global_volume),
         ],
     )
 }
 
-const MIN_VOLUME: f32 = 0.0;
-const MAX_VOLUME: f32 = 3.0;
-
-fn lower_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
-}
-
-fn raise_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() + 0.1).min(MAX_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
-}
-
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct GlobalVolumeLabel;
+struct SettingLabel(SettingKind);
 
-fn update_global_volume_label(
-    global_volume: Res<GlobalVolume>,
-    mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
-) {
-    let percent = 100.0 * global_volume.volume.to_linear();
-    label.0 = format!("{percent:3.0}%");
+fn update_setting_labels(settings: Res<Settings>, mut labels: Query<(&mut Text, &SettingLabel)>) {
+    for (mut text, label) in &mut labels {
+        text.0 = label.0.value(&settings);
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
 }
 
 fn go_back_on_click(
@@ -109,17 +204,9 @@ fn go_back_on_click(
     screen: Res<State<Screen>>,
     mut next_menu: ResMut<NextState<Menu>>,
 ) {
-    next_menu.set(if screen.get() == &Screen::Title {
-        Menu::Main
-    } else {
-        Menu::Pause
-    });
+    next_menu.set(previous_menu(screen.get()));
 }
 
 fn go_back(screen: Res<State<Screen>>, mut next_menu: ResMut<NextState<Menu>>) {
-    next_menu.set(if screen.get() == &Screen::Title {
-        Menu::Main
-    } else {
-        Menu::Pause
-    });
+    next_menu.set(previous_menu(screen.get()));
 }
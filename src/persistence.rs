@@ -0,0 +1,99 @@
+//! Files the game keeps on the player's machine, like the key bindings and the settings.
+//!
+//! They are stored as RON in [`save_dir`], a directory of the player's data.
+//! Web builds have no file system, there nothing is loaded and saving is a no-op.
+//! The same goes for tests, they don't touch the saves of the player.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use bevy::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Name of the directory of the game among the data of the player's other applications.
+const GAME_DIR: &str = "galactic-burnout";
+
+/// Directory of the saved files, e.g. `~/.local/share/galactic-burnout` on Linux, so they don't
+/// depend on where the game was launched from. Without such a directory, `saves` in the working
+/// directory is used.
+pub fn save_dir() -> &'static Path {
+    static SAVE_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+        dirs::data_dir().map_or_else(|| PathBuf::from("saves"), |dir| dir.join(GAME_DIR))
+    });
+    &SAVE_DIR
+}
+
+fn path(file_name: &str) -> PathBuf {
+    save_dir().join(file_name)
+}
+
+/// Reads a saved file. Returns `None` if it was never saved, or couldn't be read (the error is logged).
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    if cfg!(any(target_family = "wasm", test)) {
+        return None;
+    }
+
+    let path = path(file_name);
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            error!("could not read {}: {err}", path.display());
+            return None;
+        }
+    };
+
+    match ron::de::from_bytes(&bytes) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            error!("could not parse {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Writes a file, errors are logged.
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    write(
+        file_name,
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()),
+    );
+}
+
+/// Writes a file on a single line, for big files nobody edits by hand.
+pub fn save_compact<T: Serialize>(file_name: &str, value: &T) {
+    write(file_name, ron::ser::to_string(value));
+}
+
+/// Deletes a saved file, nothing happens if it doesn't exist. Errors are logged.
+pub fn remove(file_name: &str) {
+    if cfg!(any(target_family = "wasm", test)) {
+        return;
+    }
+
+    let path = path(file_name);
+    match std::fs::remove_file(&path) {
+        Ok(()) => debug!("removed {}", path.display()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => error!("could not remove {}: {err}", path.display()),
+    }
+}
+
+fn write(file_name: &str, text: ron::Result<String>) {
+    if cfg!(any(target_family = "wasm", test)) {
+        return;
+    }
+
+    let path = path(file_name);
+    let result = text.map_err(|err| err.to_string()).and_then(|text| {
+        std::fs::create_dir_all(save_dir()).map_err(|err| err.to_string())?;
+        std::fs::write(&path, text).map_err(|err| err.to_string())
+    });
+
+    match result {
+        Ok(()) => debug!("saved {}", path.display()),
+        Err(err) => error!("could not save {}: {err}", path.display()),
+    }
+}
@@ -1,47 +1,50 @@
 use avian2d::prelude::{Physics, PhysicsTime};
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
 
 use crate::player::Player;
 use crate::player::movement::AuraEarned;
 
-const BULLET_TIME_DURATION: f32 = 2.0;
+pub const BULLET_TIME_DURATION: f32 = 2.0;
 const BULLET_TIME_COOLDOWN: f32 = 1.0; // seconds
-const BULLET_TIME_AURA_COST: f32 = 100.0;
+pub const BULLET_TIME_AURA_COST: f32 = 100.0;
+/// Speed of the physics during bullet time, relative to the normal speed.
+pub const BULLET_TIME_SPEED: f32 = 0.25;
 
-pub fn reset_bullet_time(
-    player: Single<&Player>,
-    real_time: Res<Time>,
+/// Counts down bullet time on the fixed clock, so it lasts the same number of ticks in every run.
+pub fn tick_bullet_time(
+    mut player: Single<&mut Player>,
+    time: Res<Time>,
     mut physics_time: ResMut<Time<Physics>>,
-    audio: Res<Audio>,
 ) {
-    if real_time.elapsed_secs() > player.bullet_time_until {
+    let dt = time.delta_secs();
+    let was_active = player.bullet_time_left > 0.0;
+
+    player.bullet_time_left = (player.bullet_time_left - dt).max(0.0);
+    player.bullet_time_cooldown = (player.bullet_time_cooldown - dt).max(0.0);
+
+    if was_active && player.bullet_time_left <= 0.0 {
         physics_time.set_relative_speed(1.0);
-        audio.set_playback_rate(1.0);
     }
 }
 
+/// A run can end in bullet time, the next one starts at normal speed.
+pub fn reset_bullet_time(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.set_relative_speed(1.0);
+}
+
 pub fn go_into_bullet_time(
-    real_time: Res<Time>,
     mut physics_time: ResMut<Time<Physics>>,
     mut player: Single<&mut Player>,
-    audio: Res<Audio>,
     mut aura_event: MessageWriter<AuraEarned>,
 ) {
     // TODO: PLAY SOUND HERE
 
-    let rt = real_time.elapsed_secs();
-
-    if rt < player.bullet_time_until
-        || rt < player.bullet_time_cooldown_until
-        || player.aura_points < BULLET_TIME_AURA_COST
-    {
+    if !player.bullet_time_ready() || player.aura_points < BULLET_TIME_AURA_COST {
         return;
     }
-    physics_time.set_relative_speed(0.25);
-    audio.set_playback_rate(0.25);
-    player.bullet_time_until = rt + BULLET_TIME_DURATION;
-    player.bullet_time_cooldown_until = rt + BULLET_TIME_DURATION + BULLET_TIME_COOLDOWN;
+    physics_time.set_relative_speed(BULLET_TIME_SPEED);
+    player.bullet_time_left = BULLET_TIME_DURATION;
+    player.bullet_time_cooldown = BULLET_TIME_DURATION + BULLET_TIME_COOLDOWN;
     player.aura_points -= BULLET_TIME_AURA_COST;
     aura_event.write(AuraEarned(-BULLET_TIME_AURA_COST));
 }
@@ -5,15 +5,24 @@ use avian2d::prelude::{
 };
 use bevy::{math::VectorSpace, prelude::*, transform::commands};
 
-use crate::{screens::Screen, space::intro::IntroState};
+use crate::{
+    PausableSystems,
+    input::{Action, TickActions},
+    screens::Screen,
+    space::intro::IntroState,
+};
 
 use super::Player;
 
 const DASH_STRENGTH: f32 = 20000.0;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_observer(on_player_added)
-        .add_systems(Update, side_dash.run_if(in_state(Screen::Gameplay)));
+    app.add_observer(on_player_added).add_systems(
+        FixedUpdate,
+        side_dash
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
+    );
 }
 
 #[derive(Component)]
@@ -33,7 +42,7 @@ fn on_player_added(trigger: On<Add, Player>, mut commands: Commands) {
 }
 
 fn side_dash(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<TickActions>,
     player_query: Single<(Forces, &Rotation, &mut DashData)>,
     time: Res<Time>,
 ) {
@@ -43,11 +52,11 @@ fn side_dash(
         return;
     }
 
-    if keyboard_input.pressed(KeyCode::KeyQ) {
+    if actions.pressed(Action::DashLeft) {
         forces.apply_force((*rotation) * Vec2::X * -DASH_STRENGTH);
 
         dash_data.dash_timer.reset();
-    } else if keyboard_input.pressed(KeyCode::KeyE) {
+    } else if actions.pressed(Action::DashRight) {
         forces.apply_force((*rotation) * Vec2::X * DASH_STRENGTH);
 
         dash_data.dash_timer.reset();
@@ -1,30 +1,96 @@
 use avian2d::prelude::{Physics, PhysicsTime};
-use bevy::{color::palettes::css::RED, prelude::*};
+use bevy::{
+    color::palettes::css::{ORANGE_RED, RED},
+    prelude::*,
+};
 
 use crate::{
-    PausableSystems, menus::Menu, player::free::FreeMode, red_gas::ExplosionDamage, screens::Screen,
+    PausableSystems,
+    player::{Player, free::FreeMode, hull::Hull},
+    red_gas::ExplosionDamage,
+    screens::Screen,
+    simulation::is_headless,
+    space::{gas::HeatDamage, gravity::BlackHoleDamage},
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(
-        OnEnter(Screen::Gameplay),
-        (reset_damage, spawn_damage_overlay).chain(),
-    )
-    .add_systems(
-        Update,
-        check_explosion_damage
-            .run_if(in_state(Screen::Gameplay))
-            .in_set(PausableSystems),
-    );
+    app.init_resource::<PlayerDamage>()
+        .add_systems(OnEnter(Screen::Gameplay), reset_damage)
+        // on the fixed clock, so a replayed run dies on the same tick
+        .add_systems(
+            FixedUpdate,
+            check_damage
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
     // .add_systems(OnEnter(GameState::Dead), start_death_animation)
     // .add_systems(Update, animate_death.run_if(in_state(GameState::Dead)));
+
+    if !is_headless(app) {
+        app.add_systems(
+            OnEnter(Screen::Gameplay),
+            spawn_damage_overlay.after(reset_damage),
+        )
+        .add_systems(
+            Update,
+            update_damage_overlays
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
+    }
 }
 
 #[derive(Component)]
 struct DamageOverlay {}
 
-fn reset_damage(mut explosion_damage: ResMut<ExplosionDamage>) {
+/// Orange tint showing how hot the ship is.
+#[derive(Component)]
+struct HeatOverlay;
+
+/// Hazards that can kill the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DamageSource {
+    #[default]
+    Explosion,
+    Heat,
+    BlackHole,
+    Asteroid,
+}
+
+impl DamageSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageSource::Explosion => "explosion",
+            DamageSource::Heat => "heat",
+            DamageSource::BlackHole => "black hole",
+            DamageSource::Asteroid => "asteroid impact",
+        }
+    }
+}
+
+/// Fate of the ship in the current run. Every hazard keeps its own damage,
+/// the ship is destroyed by the first one to exhaust it.
+#[derive(Resource, Default, Debug)]
+pub struct PlayerDamage {
+    /// The hazard that destroyed the ship, recorded for the death screen.
+    pub killed_by: Option<DamageSource>,
+}
+
+/// Run condition that is active until the player dies.
+pub fn player_alive(damage: Res<PlayerDamage>) -> bool {
+    damage.killed_by.is_none()
+}
+
+fn reset_damage(
+    mut explosion_damage: ResMut<ExplosionDamage>,
+    mut heat_damage: ResMut<HeatDamage>,
+    mut black_hole_damage: ResMut<BlackHoleDamage>,
+    mut damage: ResMut<PlayerDamage>,
+) {
     explosion_damage.0 = 0.0;
+    heat_damage.0 = 0.0;
+    black_hole_damage.0 = 0.0;
+    *damage = PlayerDamage::default();
 }
 
 fn spawn_damage_overlay(
@@ -48,36 +114,76 @@ fn spawn_damage_overlay(
         ))
         .id();
 
-    commands.entity(*camera).add_child(damage_overlay);
+    // slightly closer to the camera, so both are visible at once
+    let heat_overlay = commands
+        .spawn((
+            HeatOverlay,
+            DespawnOnExit(Screen::Gameplay),
+            Transform::from_translation(Vec3::new(0.0, 0.0, -9.9)),
+            Mesh3d(meshes.add(Rectangle::from_length(100.))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: (ORANGE_RED * 6.).with_alpha(0.0).into(),
+                alpha_mode: AlphaMode::Blend,
+                ..Default::default()
+            })),
+        ))
+        .id();
+
+    commands
+        .entity(*camera)
+        .add_children(&[damage_overlay, heat_overlay]);
 }
 
-fn check_explosion_damage(
+fn update_damage_overlays(
     explosion_damage: Res<ExplosionDamage>,
+    heat_damage: Res<HeatDamage>,
     overlay: Single<&MeshMaterial3d<StandardMaterial>, With<DamageOverlay>>,
+    heat_overlay: Single<&MeshMaterial3d<StandardMaterial>, With<HeatOverlay>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if let Some(material) = materials.get_mut(*overlay) {
+        material.base_color.set_alpha(explosion_damage.0);
+        // material.alpha_mode = AlphaMode::Mask(explosion_damage.0);
+    }
+
+    if let Some(material) = materials.get_mut(*heat_overlay) {
+        material.base_color.set_alpha(heat_damage.0 * 0.6);
+    }
+}
+
+/// Destroys the ship once the hull is gone or any other hazard reached 1.0,
+/// that hazard is recorded as the cause of death.
+fn check_damage(
+    explosion_damage: Res<ExplosionDamage>,
+    heat_damage: Res<HeatDamage>,
+    black_hole_damage: Res<BlackHoleDamage>,
+    hull: Single<&Hull, With<Player>>,
+    mut damage: ResMut<PlayerDamage>,
 
-    _menu_state: ResMut<NextState<Menu>>,
     mut screen_state: ResMut<NextState<Screen>>,
 
     mut phys_time: ResMut<Time<Physics>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     free_mode: Res<State<FreeMode>>,
 ) {
-    let Some(material) = materials.get_mut(*overlay) else {
-        return;
-    };
-
-    material.base_color.set_alpha(explosion_damage.0);
-    // material.alpha_mode = AlphaMode::Mask(explosion_damage.0);
-
     if free_mode.0 {
         return;
     }
 
-    if explosion_damage.0 >= 1.0 {
-        phys_time.pause();
-        // menu_state.set(Menu::Dead);
-        screen_state.set(Screen::Dead);
-    }
+    let exhausted = [
+        (DamageSource::Asteroid, hull.is_destroyed()),
+        (DamageSource::Explosion, explosion_damage.0 >= 1.0),
+        (DamageSource::Heat, heat_damage.0 >= 1.0),
+        (DamageSource::BlackHole, black_hole_damage.0 >= 1.0),
+    ];
+    let Some((source, _)) = exhausted.into_iter().find(|(_, exhausted)| *exhausted) else {
+        return;
+    };
+
+    info!("killed by {}", source.name());
+    damage.killed_by = Some(source);
+    phys_time.pause();
+    // menu_state.set(Menu::Dead);
+    screen_state.set(Screen::Dead);
 }
 
 // fn start_death_animation(mut death_animation: ResMut<DeathAnimation>) {
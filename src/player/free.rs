@@ -2,6 +2,8 @@ use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
 use bevy::dev_tools::states::log_transitions;
 
+use crate::simulation::is_headless;
+
 #[cfg(feature = "dev")]
 use crate::utils::toggle_vsync;
 
@@ -22,6 +24,11 @@ fn toggle_free_mode(
 pub fn plugin(app: &mut App) {
     app.insert_state(FreeMode(false));
 
+    // there's no keyboard nor window to toggle anything with
+    if is_headless(app) {
+        return;
+    }
+
     #[cfg(feature = "dev")]
     app.add_systems(
         Update,
@@ -0,0 +1,341 @@
+//! Ghost of the best run on the current seed, to race against.
+//!
+//! The ship is recorded every few simulated ticks. When a run beats the best score of its seed,
+//! its trajectory is saved, and the next runs on that seed show it as a translucent ship
+//! flying along, with the difference of score and time in the HUD.
+//! Only the ghosts of the [`MAX_GHOSTS`] seeds beaten last are kept, the older ones are deleted.
+
+use std::f32::consts::{PI, TAU};
+
+use avian2d::prelude::{Position, Rotation};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    PausableSystems, persistence,
+    replay::RunReplay,
+    screens::Screen,
+    settings::Settings,
+    space::{WorldSeed, reset_world},
+};
+
+use super::{Player, Score, assets::PlayerAssets};
+
+/// Ticks between two recorded frames, the ghost is interpolated in between.
+const FRAME_INTERVAL: u32 = 8;
+/// Seeds keeping the ghost of their best run.
+pub const MAX_GHOSTS: usize = 10;
+/// The seeds with a saved ghost, the most recently saved first.
+const GHOSTS_FILE: &str = "ghosts.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GhostRecording>()
+        .init_resource::<BestGhost>()
+        .init_resource::<GhostDelta>()
+        .add_systems(
+            OnEnter(Screen::Gameplay),
+            (start_recording, spawn_ghost).chain().after(reset_world),
+        )
+        .add_systems(OnEnter(Screen::Dead), save_best_ghost)
+        .add_systems(
+            FixedUpdate,
+            (record_ship, follow_ghost)
+                .chain()
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        )
+        .add_systems(Update, toggle_ghost.run_if(resource_changed::<Settings>));
+}
+
+/// The ship on one tick of a run.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GhostFrame {
+    pub pos: Vec2,
+    pub angle: f32,
+    pub score: f32,
+}
+
+impl GhostFrame {
+    /// In between the two frames, `t` going from 0.0 (`self`) to 1.0 (`next`).
+    fn lerp(&self, next: &GhostFrame, t: f32) -> GhostFrame {
+        // the short way around
+        let turn = (next.angle - self.angle + PI).rem_euclid(TAU) - PI;
+        GhostFrame {
+            pos: self.pos.lerp(next.pos, t),
+            angle: self.angle + turn * t,
+            score: self.score + (next.score - self.score) * t,
+        }
+    }
+}
+
+/// The trajectory of a run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GhostRun {
+    pub score: f32,
+    /// One every [`FRAME_INTERVAL`] ticks, from the first tick.
+    pub frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    /// Each seed has its own file, only the one being played is loaded.
+    fn file_name(seed: WorldSeed) -> String {
+        format!("ghost_{}.ron", seed.0)
+    }
+
+    /// The best run saved for the seed, if any.
+    pub fn load(seed: WorldSeed) -> Option<Self> {
+        persistence::load(&Self::file_name(seed))
+    }
+
+    /// Saves the run as the ghost of the seed, and deletes the ghost of the seed
+    /// beaten the longest ago if there are too many.
+    pub fn save(&self, seed: WorldSeed) {
+        persistence::save_compact(&Self::file_name(seed), self);
+
+        let mut seeds: Vec<u32> = persistence::load(GHOSTS_FILE).unwrap_or_default();
+        seeds.retain(|saved| *saved != seed.0);
+        seeds.insert(0, seed.0);
+        let evicted = seeds.split_off(seeds.len().min(MAX_GHOSTS));
+        for seed in evicted {
+            persistence::remove(&Self::file_name(WorldSeed(seed)));
+        }
+        persistence::save(GHOSTS_FILE, &seeds);
+    }
+
+    /// Adds the ship on the next tick, only one tick in [`FRAME_INTERVAL`] is kept.
+    pub fn record(&mut self, tick: u32, frame: GhostFrame) {
+        if tick.is_multiple_of(FRAME_INTERVAL) {
+            self.frames.push(frame);
+        }
+        self.score = frame.score;
+    }
+
+    /// Where the ghost is on the tick, it stays at its last position once its run is over.
+    pub fn frame(&self, tick: u32) -> Option<GhostFrame> {
+        let index = (tick / FRAME_INTERVAL) as usize;
+        match (self.frames.get(index), self.frames.get(index + 1)) {
+            (Some(frame), Some(next)) => {
+                let t = (tick % FRAME_INTERVAL) as f32 / FRAME_INTERVAL as f32;
+                Some(frame.lerp(next, t))
+            }
+            _ => self.frames.last().copied(),
+        }
+    }
+
+    /// First tick the ghost reached the score, `None` if it never did.
+    /// In between two frames, it's when the interpolated score reaches it.
+    pub fn tick_reaching(&self, score: f32) -> Option<f32> {
+        // the score never goes down
+        let index = self.frames.partition_point(|frame| frame.score < score);
+        let frame = self.frames.get(index)?;
+        let Some(previous) = index.checked_sub(1).map(|i| &self.frames[i]) else {
+            return Some(0.0);
+        };
+
+        let t = (score - previous.score) / (frame.score - previous.score);
+        Some((index as f32 - 1.0 + t) * FRAME_INTERVAL as f32)
+    }
+}
+
+/// Trajectory of the current run.
+#[derive(Resource, Default, Debug)]
+pub struct GhostRecording {
+    pub run: GhostRun,
+    /// Ticks recorded, including the ones left out of the frames.
+    pub ticks: u32,
+}
+
+/// Best run of the current seed, raced by the ghost ship.
+#[derive(Resource, Default, Debug)]
+pub struct BestGhost(pub Option<GhostRun>);
+
+/// How the current run compares to the ghost, `None` without a ghost.
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct GhostDelta(pub Option<Delta>);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Delta {
+    /// Score of the player minus the score of the ghost on the same tick.
+    pub score: f32,
+    /// How much later than the ghost the player reached their score, negative when ahead.
+    /// `None` once the player beats the final score of the ghost.
+    pub time_secs: Option<f32>,
+}
+
+/// The translucent ship following the [`BestGhost`].
+#[derive(Component)]
+pub struct GhostShip;
+
+fn start_recording(
+    mut recording: ResMut<GhostRecording>,
+    mut best: ResMut<BestGhost>,
+    mut delta: ResMut<GhostDelta>,
+    seed: Res<WorldSeed>,
+) {
+    *recording = GhostRecording::default();
+    best.0 = GhostRun::load(*seed);
+    delta.0 = None;
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    best: Res<BestGhost>,
+    settings: Res<Settings>,
+    player_assets: Res<PlayerAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(start) = best.0.as_ref().and_then(|ghost| ghost.frame(0)) else {
+        return;
+    };
+
+    commands.spawn((
+        Name::new("Ghost Ship"),
+        GhostShip,
+        DespawnOnExit(Screen::Gameplay),
+        Mesh3d(player_assets.ship.clone()),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgba(0.7, 0.8, 1.0, 0.25),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        })),
+        ghost_transform(start),
+        ghost_visibility(settings.ghost),
+    ));
+}
+
+fn ghost_transform(frame: GhostFrame) -> Transform {
+    Transform::from_translation(frame.pos.extend(0.0))
+        .with_rotation(Quat::from_rotation_z(frame.angle))
+}
+
+fn ghost_visibility(enabled: bool) -> Visibility {
+    if enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_ghost(settings: Res<Settings>, mut ghosts: Query<&mut Visibility, With<GhostShip>>) {
+    for mut visibility in &mut ghosts {
+        *visibility = ghost_visibility(settings.ghost);
+    }
+}
+
+fn record_ship(
+    mut recording: ResMut<GhostRecording>,
+    player: Single<(&Position, &Rotation), With<Player>>,
+    score: Res<Score>,
+) {
+    let (pos, rotation) = player.into_inner();
+    let tick = recording.ticks;
+    recording.run.record(
+        tick,
+        GhostFrame {
+            pos: pos.0,
+            angle: rotation.as_radians(),
+            score: score.0,
+        },
+    );
+    recording.ticks += 1;
+}
+
+fn follow_ghost(
+    recording: Res<GhostRecording>,
+    best: Res<BestGhost>,
+    settings: Res<Settings>,
+    mut delta: ResMut<GhostDelta>,
+    mut ghost_ship: Query<&mut Transform, With<GhostShip>>,
+    time: Res<Time>,
+) {
+    let (Some(ghost), Some(tick)) = (&best.0, recording.ticks.checked_sub(1)) else {
+        return;
+    };
+    let Some(frame) = ghost.frame(tick) else {
+        return;
+    };
+
+    for mut tr in &mut ghost_ship {
+        *tr = ghost_transform(frame);
+    }
+
+    let score = recording.run.score;
+    delta.0 = settings.ghost.then(|| Delta {
+        score: score - frame.score,
+        time_secs: ghost
+            .tick_reaching(score)
+            .map(|ghost_tick| (tick as f32 - ghost_tick) * time.delta_secs()),
+    });
+}
+
+/// Keeps the run as the ghost of its seed if it beat the previous best.
+/// A replay flies the same trajectory as the run it recorded, it's not saved again.
+fn save_best_ghost(
+    recording: Res<GhostRecording>,
+    best: Res<BestGhost>,
+    run_replay: Res<RunReplay>,
+    seed: Res<WorldSeed>,
+) {
+    let beaten = best
+        .0
+        .as_ref()
+        .is_none_or(|best| recording.run.score > best.score);
+    if beaten && !run_replay.is_playing() && !recording.run.frames.is_empty() {
+        info!("new best run on seed {}, saving its ghost", seed.0);
+        recording.run.save(*seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flies right at 1 unit and 1 point per tick.
+    fn straight_run(ticks: u32) -> GhostRun {
+        let mut run = GhostRun::default();
+        for tick in 0..ticks {
+            let t = tick as f32;
+            run.record(
+                tick,
+                GhostFrame {
+                    pos: Vec2::new(t, 0.0),
+                    angle: 0.0,
+                    score: t,
+                },
+            );
+        }
+        run
+    }
+
+    #[test]
+    fn ghost_is_interpolated_between_frames() {
+        let run = straight_run(100);
+        assert_eq!(
+            run.frames.len(),
+            100_usize.div_ceil(FRAME_INTERVAL as usize)
+        );
+
+        let frame = run.frame(FRAME_INTERVAL + 3).unwrap();
+        assert!((frame.pos.x - (FRAME_INTERVAL + 3) as f32).abs() < 1e-4);
+        assert!((frame.score - (FRAME_INTERVAL + 3) as f32).abs() < 1e-4);
+        // past the end it waits on the last frame
+        assert_eq!(run.frame(1000), run.frames.last().copied());
+        assert!((run.tick_reaching(21.5).unwrap() - 21.5).abs() < 1e-4);
+        assert_eq!(run.tick_reaching(1000.0), None);
+    }
+
+    #[test]
+    fn ghost_turns_the_short_way() {
+        let from = GhostFrame {
+            pos: Vec2::ZERO,
+            angle: PI - 0.1,
+            score: 0.0,
+        };
+        let to = GhostFrame {
+            angle: -PI + 0.1,
+            ..from
+        };
+        assert!((from.lerp(&to, 0.5).angle - PI).abs() < 1e-4);
+    }
+}
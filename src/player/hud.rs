@@ -5,8 +5,14 @@ use bevy::prelude::*;
 use crate::{
     PausableSystems,
     asset_tracking::LoadResource,
-    player::{Player, Score, movement::AuraEarned},
+    player::{
+        Player, Score,
+        ghost::GhostDelta,
+        hull::Hull,
+        movement::{AuraEarned, CurrentGas},
+    },
     screens::Screen,
+    space::gas::kind::FuelMix,
     theme::widget,
 };
 
@@ -27,7 +33,7 @@ fn setup_hud(mut commands: Commands, assets: Res<HudAssets>) {
         widget::ui_root_uncentered("Scores"),
         DespawnOnExit(Screen::Gameplay),
         children![(
-            widget::label("Score:\nAura:"),
+            widget::label("Score:\nAura:\nHull:\nFuel:"),
             Node {
                 align_self: AlignSelf::Start,
                 left: Val::Px(10.0),
@@ -58,14 +64,16 @@ fn setup_hud(mut commands: Commands, assets: Res<HudAssets>) {
 
 // TODO: DASH ABILITY
 fn update_hud(
-    player: Single<&Player>,
+    player: Single<(&Player, &Hull, &CurrentGas, &FuelMix)>,
     mut score_text: Single<&mut Text, (With<HudScores>, Without<HudAbilities>)>,
     mut abilities_text: Single<&mut Text, (With<HudAbilities>, Without<HudScores>)>,
     score: Res<Score>,
+    ghost_delta: Res<GhostDelta>,
     time: Res<Time>,
     mut aura_event: MessageReader<AuraEarned>,
     mut recent_earnings: Local<VecDeque<(f32, u32)>>,
 ) {
+    let (player, hull, current_gas, fuel_mix) = player.into_inner();
     let mut earnings = String::new();
     let ct = time.elapsed().as_millis() as u32;
 
@@ -96,14 +104,30 @@ fn update_hud(
         ));
     }
 
+    // the gas currently fueling the engine, ignoring the last traces of it
+    let fuel = match fuel_mix.dominant() {
+        Some(kind) if current_gas.0 > 0.05 => kind.name(),
+        _ => "-",
+    };
+
+    // ahead of the ghost is a higher score and a negative time, like split times
+    let ghost = match ghost_delta.0 {
+        Some(delta) => match delta.time_secs {
+            Some(time) => format!("Ghost: {:+.1} ({time:+.1}s)\n", delta.score),
+            None => format!("Ghost: {:+.1}\n", delta.score),
+        },
+        None => String::new(),
+    };
+
     score_text.0 = format!(
-        "Score: {:.1}\nAura: {}\n{earnings}",
-        score.0, player.aura_points as i32,
+        "Score: {:.1}\nAura: {}\nHull: {:.0}%\nFuel: {fuel}\n{ghost}{earnings}",
+        score.0,
+        player.aura_points as i32,
+        hull.hp / hull.max_hp * 100.0,
     );
     let mut abilities_string = "Bullet time:".to_string();
-    let elapsed = time.elapsed_secs();
     let mut has_abilities = false;
-    if player.bullet_time_cooldown_until < elapsed && player.bullet_time_until < elapsed {
+    if player.bullet_time_ready() {
         abilities_string.push('⏳');
         has_abilities = true;
     }
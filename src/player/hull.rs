@@ -0,0 +1,88 @@
+//! Structural integrity of the ship. Impacts wear the hull down, the ship breaks apart when it's gone.
+
+use avian2d::prelude::{Physics, PhysicsTime};
+use bevy::prelude::*;
+
+use crate::{PausableSystems, screens::Screen};
+
+use super::Player;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Hull>().add_systems(
+        Update,
+        blink_while_invulnerable
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
+    );
+}
+
+/// Hit points of a fresh hull.
+pub const HULL_HP: f32 = 100.0;
+/// Impacts slower than this (relative to the obstacle) only scratch the paint.
+const MIN_IMPACT_SPEED: f32 = 50.0;
+/// Hit points lost per unit of impact speed above [`MIN_IMPACT_SPEED`].
+const DAMAGE_PER_IMPACT_SPEED: f32 = 0.12;
+/// Duration of the invulnerability following a hit.
+const INVULNERABILITY_SECS: f32 = 1.0;
+
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Hull {
+    pub hp: f32,
+    pub max_hp: f32,
+    /// Physics time (in seconds) until which the hull can't be damaged.
+    pub invulnerable_until: f32,
+}
+
+impl Default for Hull {
+    fn default() -> Self {
+        Self {
+            hp: HULL_HP,
+            max_hp: HULL_HP,
+            invulnerable_until: 0.0,
+        }
+    }
+}
+
+impl Hull {
+    /// Hit points an impact at the given relative speed takes away.
+    pub fn impact_damage(relative_speed: f32) -> f32 {
+        (relative_speed - MIN_IMPACT_SPEED).max(0.0) * DAMAGE_PER_IMPACT_SPEED
+    }
+
+    pub fn is_invulnerable(&self, now: f32) -> bool {
+        now < self.invulnerable_until
+    }
+
+    /// Applies the damage, unless the hull is still recovering from the previous hit.
+    /// Returns the hit points actually lost.
+    pub fn hit(&mut self, damage: f32, now: f32) -> f32 {
+        if self.is_invulnerable(now) || damage <= 0.0 {
+            return 0.0;
+        }
+
+        let lost = damage.min(self.hp);
+        self.hp -= lost;
+        self.invulnerable_until = now + INVULNERABILITY_SECS;
+        lost
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.hp <= 0.0
+    }
+}
+
+/// The ship blinks while it can't be damaged.
+fn blink_while_invulnerable(
+    player: Single<(&Hull, &mut Visibility), With<Player>>,
+    time: Res<Time<Physics>>,
+) {
+    let (hull, mut visibility) = player.into_inner();
+    let now = time.elapsed_secs();
+
+    let hidden = hull.is_invulnerable(now) && (now * 10.0) as u32 % 2 == 0;
+    visibility.set_if_neq(if hidden {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    });
+}
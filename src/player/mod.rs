@@ -1,10 +1,12 @@
 use avian2d::prelude::{LinearVelocity, Physics, PhysicsTime, Rotation};
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy::prelude::*;
 
-use crate::player::abilities::{go_into_bullet_time, reset_bullet_time};
+use crate::PausableSystems;
+use crate::input::{Action, tick_action_just_pressed};
+use crate::player::abilities::{go_into_bullet_time, reset_bullet_time, tick_bullet_time};
 use crate::player::movement::AuraEarned;
 use crate::screens::Screen;
+use crate::simulation::is_headless;
 use crate::space::intro::IntroState;
 
 pub mod abilities;
@@ -13,7 +15,9 @@ pub mod dash;
 pub mod death;
 pub mod engine;
 pub mod free;
+pub mod ghost;
 pub mod hud;
+pub mod hull;
 pub mod movement;
 pub mod sound;
 pub mod spawn;
@@ -23,29 +27,40 @@ pub(super) fn plugin(app: &mut App) {
         movement::plugin,
         spawn::plugin,
         assets::plugin,
-        engine::plugin,
-        hud::plugin,
         death::plugin,
         dash::plugin,
-        sound::plugin,
         free::plugin,
     ))
     .add_systems(
-        Update,
+        FixedUpdate,
         (
-            go_into_bullet_time.run_if(input_just_pressed(KeyCode::Space)),
-            reset_bullet_time,
+            tick_bullet_time,
+            go_into_bullet_time.run_if(tick_action_just_pressed(Action::BulletTime)),
         )
-            .run_if(in_state(Screen::Gameplay)),
-    )
-    .add_systems(
-        FixedPostUpdate,
-        camera_follow_player.run_if(in_state(IntroState(false))), // avian docs suggests this as well, but idk
-                                                                  // .before(TransformSystems::Propagate),
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
     );
 
+    if !is_headless(app) {
+        app.add_plugins((
+            engine::plugin,
+            hud::plugin,
+            hull::plugin,
+            sound::plugin,
+            ghost::plugin,
+        ))
+        .add_systems(
+            FixedPostUpdate,
+            camera_follow_player.run_if(in_state(IntroState(false))), // avian docs suggests this as well, but idk
+                                                                      // .before(TransformSystems::Propagate),
+        );
+    }
+
     app.insert_resource(Score(0.0));
 
+    app.add_systems(OnEnter(Screen::Gameplay), reset_bullet_time);
+
     app.add_systems(OnEnter(Screen::Gameplay), |mut score: ResMut<Score>| {
         score.0 = 0.0
     });
@@ -57,11 +72,17 @@ pub struct Score(pub f32);
 #[derive(Component, Default)]
 pub struct Player {
     pub aura_points: f32, // given based on style (flying by objects at high speeds, etc.)
-    pub bullet_time_until: f32, // seconds
-    pub bullet_time_cooldown_until: f32, // seconds
+    pub bullet_time_left: f32, // seconds
+    pub bullet_time_cooldown: f32, // seconds, until bullet time can be used again
     pub near_asteroids: bool,
 }
 
+impl Player {
+    pub fn bullet_time_ready(&self) -> bool {
+        self.bullet_time_left <= 0.0 && self.bullet_time_cooldown <= 0.0
+    }
+}
+
 pub fn camera_follow_player(
     q_camera: Single<&mut Transform, With<Camera>>,
     q_player: Single<(&GlobalTransform, &LinearVelocity), With<Player>>,
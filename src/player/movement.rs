@@ -2,16 +2,15 @@ use avian2d::math::Scalar;
 use avian2d::prelude::*;
 use bevy::color::palettes::css::GREEN_YELLOW;
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl as _};
 
 // use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore};
 
 use crate::PausableSystems;
-use crate::audio::AudioAssets;
+use crate::input::{Action, TickActions};
 use crate::player::Score;
 use crate::screens::Screen;
 use crate::space::GasGenerator;
-use crate::space::gas::ignite_gas;
+use crate::space::gas::{ignite_gas, kind::FuelMix};
 
 use super::Player;
 
@@ -50,7 +49,7 @@ pub struct AuraEarned(pub f32);
 
 // *maybe rename this function
 pub fn thrust(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<TickActions>,
     player_query: Single<
         (
             &mut Player,
@@ -58,6 +57,7 @@ pub fn thrust(
             // &mut ExternalForce,
             // &mut ExternalTorque,
             &mut CurrentGas,
+            &mut FuelMix,
             &Rotation,
             &MovementAcceleration,
             &RotationSpeed,
@@ -68,12 +68,8 @@ pub fn thrust(
     time: Res<Time<Physics>>,
     mut score: ResMut<Score>,
     mut aura_event: MessageWriter<AuraEarned>,
-    audio: Res<Audio>,
-    audio_assets: Res<AudioAssets>,
 ) {
-    let left = keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
-    let right = keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]);
-    let brake = keyboard_input.pressed(KeyCode::KeyS);
+    let brake = actions.pressed(Action::Brake);
 
     let (
         mut player,
@@ -81,6 +77,7 @@ pub fn thrust(
         // mut force,
         // mut torque,
         mut current_gas,
+        mut fuel_mix,
         rotation,
         acceleration,
         rotation_speed,
@@ -97,8 +94,6 @@ pub fn thrust(
         player.aura_points += earned;
         aura_event.write(AuraEarned(earned));
         debug!("earned: {earned}");
-
-        audio.play(audio_assets.pop_3.clone()).with_volume(0.06);
     }
     player.aura_points += 1.0 * delta;
 
@@ -107,15 +102,15 @@ pub fn thrust(
     // force.persistent = false;
     // torque.persistent = false;
 
+    // the kind of gas in the engine changes how the ship handles, proportionally to how much gas is left
+    let gas_params = fuel_mix.params();
+    let turning = 1.0 + (gas_params.turning - 1.0) * current_gas.0;
+
     let speed_sqrt = vel_length.sqrt();
     debug!("sqrt(speed) = {speed_sqrt:.2}",);
-    let tq = rotation_speed.0 / speed_sqrt.max(SPEED_LOCK_IN);
-    if left {
-        forces.apply_torque(tq);
-    }
-    if right {
-        forces.apply_torque(-tq);
-    }
+    let tq = rotation_speed.0 * turning / speed_sqrt.max(SPEED_LOCK_IN);
+    // positive turn is to the left, gamepads can turn partially
+    forces.apply_torque(tq * actions.turn());
 
     let forward_dir = rotation * Vec2::Y;
 
@@ -125,13 +120,17 @@ pub fn thrust(
         thrust_force *= 0.15;
     }
 
-    let gas_boost_force = forward_dir * current_gas.0 * gas_boost.0;
+    let gas_boost_force = forward_dir * current_gas.0 * gas_boost.0 * gas_params.boost;
+    let gas_drag_force = -forces.linear_velocity() * current_gas.0 * gas_params.drag;
 
     forces.apply_force(thrust_force);
     forces.apply_force(gas_boost_force);
+    forces.apply_force(gas_drag_force);
 
     let before = current_gas.0;
-    current_gas.0 *= 0.01f32.powf(delta);
+    let burnt = gas_params.burn_rate.powf(delta);
+    current_gas.0 *= burnt;
+    fuel_mix.scale(burnt);
     debug!("current_gas: {before:.2} -> {:.2}", current_gas.0);
 }
 
@@ -1,31 +1,44 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween, prelude::Decibels};
+use bevy_kira_audio::{AudioChannel, AudioControl, AudioInstance, AudioTween, prelude::Decibels};
 // use kira::Volume
 
-use crate::{audio::AudioAssets, player::Player};
+use crate::{
+    audio::{AmbienceChannel, AudioAssets, SfxChannel, linear_to_decibels},
+    player::{Player, movement::AuraEarned},
+    settings::Settings,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(setup_sound)
         .add_observer(stop_sound)
-        .add_systems(Update, update_sound);
+        .add_systems(Update, (update_sound, pop_aura));
 }
 
 #[derive(Component)]
 pub struct EngineSound(Handle<AudioInstance>);
 
 const VOLUME: f32 = -20.0;
+/// Linear volume of the pop of earned aura.
+const POP_VOLUME: f32 = 0.06;
+
+/// Adds the effects volume of the settings to the engine volume,
+/// the volume of an instance replaces the one of the channel.
+fn engine_volume(decibels: f32, settings: &Settings) -> Decibels {
+    Decibels(decibels + linear_to_decibels(settings.sfx_gain()).0)
+}
 
 fn setup_sound(
     trigger: On<Add, Player>,
     mut cmds: Commands,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<AmbienceChannel>>,
     audio_assets: Res<AudioAssets>,
+    settings: Res<Settings>,
 ) {
     let sound = audio
         .play(audio_assets.engine_fire.clone())
-        .with_volume(Decibels(VOLUME))
+        .with_volume(engine_volume(VOLUME, &settings))
         // .with_volume(Volume::Amplitude(VOLUME as f64))
         .looped()
         .handle();
@@ -37,6 +50,7 @@ fn update_sound(
     q_sound: Single<&EngineSound>,
     q_camera: Single<&GlobalTransform, With<Camera>>,
     mut sounds: ResMut<Assets<AudioInstance>>,
+    settings: Res<Settings>,
     time: Res<Time>,
 ) {
     let sound = q_sound.into_inner();
@@ -47,7 +61,7 @@ fn update_sound(
     // TODO: This might behave differently from the way it did with
     // Volume::Amplitude. Will need adjusting when we can run the game.
     instance.set_decibels(
-        Decibels(VOLUME * (cam_tr.translation().z / 500.0)),
+        engine_volume(VOLUME * (cam_tr.translation().z / 500.0), &settings),
         AudioTween::linear(Duration::from_secs_f32(time.delta_secs())),
     );
 }
@@ -65,3 +79,21 @@ fn stop_sound(
     };
     instance.stop(AudioTween::linear(Duration::from_secs(1)));
 }
+
+/// Pops once per frame while aura is being earned, however many ticks earned it.
+fn pop_aura(
+    mut aura_events: MessageReader<AuraEarned>,
+    audio: Res<AudioChannel<SfxChannel>>,
+    audio_assets: Res<AudioAssets>,
+    settings: Res<Settings>,
+) {
+    // bullet time costs aura, that's not a pop
+    let most_earned = aura_events
+        .read()
+        .fold(0.0_f32, |most, event| most.max(event.0));
+    if most_earned > 0.0 {
+        audio
+            .play(audio_assets.pop_3.clone())
+            .with_volume(linear_to_decibels(POP_VOLUME * settings.sfx_gain()));
+    }
+}
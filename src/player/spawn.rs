@@ -1,12 +1,16 @@
 use avian2d::prelude::*;
 use bevy::{color::palettes::css::VIOLET, prelude::*};
 
-use crate::{asteroids::ShipAsteroidCollider, player::movement::CurrentGas, screens::Screen};
+use crate::{
+    asteroids::ShipAsteroidCollider, player::movement::CurrentGas, screens::Screen,
+    space::gas::kind::FuelMix,
+};
 
 use super::{
     Player,
     assets::PlayerAssets,
     engine,
+    hull::Hull,
     movement::{GasBoost, MovementAcceleration, RotationSpeed},
 };
 
@@ -15,7 +19,7 @@ pub(crate) fn plugin(app: &mut App) {
         .add_systems(OnExit(Screen::Gameplay), despawn_player);
 }
 
-fn spawn_player(
+pub fn spawn_player(
     mut commands: Commands,
     player_assets: Res<PlayerAssets>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -44,6 +48,8 @@ fn spawn_player(
                 RotationSpeed(1000.0),
             ),
             (
+                FuelMix::default(),
+                Hull::default(),
                 GravityScale(0.001),
                 PointLight {
                     color: VIOLET.lighter(0.5).into(),
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{f32::consts::FRAC_PI_2, time::Duration};
 
 use avian2d::prelude::{Physics, PhysicsTime};
 use bevy::{
@@ -21,11 +21,13 @@ use crate::{
     player::Player,
     red_gas::{
         EXPLOSION_DURATION_SECS, ExplosionDamage, MAX_EXPLOSION_RADIUS, PhysicalTimeAnimator,
-        RedGasOrb, RedOrbExplosion, RedOrbExplosionEvent, RedOrbExplosionLens,
-        assets::RedOrbAssets,
+        RedGasOrb, RedOrbExplosion, RedOrbExplosionEvent, assets::RedOrbAssets, mix,
     },
     screens::Screen,
-    space::intro::IntroState,
+    space::{
+        delta::{ChunkDeltas, ChunkItem},
+        intro::IntroState,
+    },
     utils::PointLightLens,
 };
 
@@ -53,19 +55,23 @@ pub fn explode_red_orbs(
     mut events: MessageReader<RedOrbExplosionEvent>,
     mut commands: Commands,
 
-    orbs: Query<&RedGasOrb>,
+    orbs: Query<(&RedGasOrb, Option<&ChunkItem>)>,
     orb_assets: Res<RedOrbAssets>,
+    mut deltas: ResMut<ChunkDeltas>,
 
     // debug
     #[cfg(feature = "dev")] player: Single<&Transform, With<Player>>,
     #[cfg(feature = "dev")] mut gizmo: Gizmos,
 ) {
     for event in events.read() {
-        let Ok(orb) = orbs.get(event.entity) else {
+        let Ok((orb, chunk_item)) = orbs.get(event.entity) else {
             continue;
         };
 
         commands.entity(event.entity).try_despawn();
+        if let Some(item) = chunk_item {
+            deltas.record(item);
+        }
 
         // debug
         #[cfg(feature = "dev")]
@@ -85,26 +91,11 @@ pub fn explode_red_orbs(
             }
         }
 
-        let explosion_radius_tween = Tween::new(
-            EaseFunction::SineOut,
-            Duration::from_secs(EXPLOSION_DURATION_SECS),
-            RedOrbExplosionLens {
-                radius_start: orb.radius,
-                radius_end: MAX_EXPLOSION_RADIUS,
-            },
-        );
-
         commands
             .spawn((
                 DespawnOnExit(Screen::Gameplay),
-                RedOrbExplosion {
-                    pos: orb.pos.xy(),
-                    radius: orb.radius,
-                    interactions: 0,
-                },
+                RedOrbExplosion::new(orb.pos.xy(), orb.radius),
                 Transform::from_translation(orb.pos),
-                PhysicalTimeAnimator {},
-                TweenAnim::new(explosion_radius_tween),
                 Visibility::Visible,
             ))
             .with_children(|builder| {
@@ -114,13 +105,33 @@ pub fn explode_red_orbs(
                     orb_assets.explosion_mesh.clone(),
                     orb_assets.explosion_material.clone(),
                 );
-            })
-            .observe(|trigger: On<AnimCompletedEvent>, mut commands: Commands| {
-                commands.entity(trigger.event_target()).try_despawn();
             });
     }
 }
 
+/// Grows the explosions on the physics clock, so bullet time slows them down
+/// and an explosion reaches the same size on the same tick in every run.
+/// They fade away after [`EXPLOSION_DURATION_SECS`].
+pub fn grow_explosions(
+    mut commands: Commands,
+    mut explosions: Query<(Entity, &mut RedOrbExplosion)>,
+    time: Res<Time<Physics>>,
+) {
+    let duration = EXPLOSION_DURATION_SECS as f32;
+
+    for (entity, mut explosion) in &mut explosions {
+        explosion.age += time.delta_secs();
+        if explosion.age >= duration {
+            commands.entity(entity).try_despawn();
+            continue;
+        }
+
+        // sine out, fast at first and slowing down
+        let t = (explosion.age / duration * FRAC_PI_2).sin();
+        explosion.radius = mix(explosion.start_radius, MAX_EXPLOSION_RADIUS, t);
+    }
+}
+
 pub fn spawn_explosion_mesh(
     builder: &mut RelatedSpawnerCommands<'_, ChildOf>,
 
@@ -6,9 +6,10 @@ use bevy::{
     prelude::*,
 };
 use bevy_spatial::{AutomaticUpdate, SpatialStructure, TransformMode};
-use bevy_tweening::{AnimationSystem, Lens};
 
-use crate::{PausableSystems, asset_tracking::LoadResource, screens::Screen};
+use crate::{
+    PausableSystems, asset_tracking::LoadResource, screens::Screen, simulation::is_headless,
+};
 
 pub mod assets;
 pub mod logic;
@@ -22,38 +23,46 @@ const EXPLOSION_DURATION_SECS: u64 = 10;
 const EXPLOSION_CLEANUP_RADIUS: f32 = 3000.;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((
+    app.add_plugins(
         AutomaticUpdate::<RedGasOrb>::new()
+            .with_schedule(FixedUpdate)
             .with_spatial_ds(SpatialStructure::KDTree2)
             .with_frequency(Duration::from_secs_f32(0.1))
             .with_transform(TransformMode::GlobalTransform),
-        sound::plugin,
-    ))
+    )
     .add_observer(on_add_explosive_gas_orb)
     .load_resource::<RedOrbAssets>()
     .insert_resource(ExplosionDamage(0.0))
     .add_message::<RedOrbExplosionEvent>()
     .add_systems(
         FixedUpdate,
-        (explode_red_orbs, check_explosion_interactions)
-            .run_if(in_state(Screen::Gameplay))
-            .in_set(PausableSystems),
-    )
-    .add_systems(
-        Update,
         (
-            update_component_animator_speed,
-            // update_asset_animator_speed::<StandardMaterial>,
+            grow_explosions,
+            explode_red_orbs,
+            check_explosion_interactions,
         )
-            .run_if(in_state(Screen::Gameplay)),
+            .chain()
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
     );
+
+    if !is_headless(app) {
+        app.add_plugins(sound::plugin).add_systems(
+            Update,
+            (
+                update_component_animator_speed,
+                // update_asset_animator_speed::<StandardMaterial>,
+            )
+                .run_if(in_state(Screen::Gameplay)),
+        );
+    }
 }
 
 #[derive(Resource)]
-/// When the damage reaches 1.0, the player must die.
+/// Damage from red gas explosions. When it reaches 1.0, the player must die.
 pub struct ExplosionDamage(pub f32);
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug)]
 pub struct RedGasOrb {
     pub radius: f32,
     pub pos: Vec3,
@@ -61,10 +70,26 @@ pub struct RedGasOrb {
 
 #[derive(Component)]
 pub struct RedOrbExplosion {
-    radius: f32,
-    pos: Vec2,
+    pub radius: f32,
+    pub pos: Vec2,
     // The number of other orbs this one has interacted with. For optimization purposes.
     interactions: usize,
+    /// Radius of the orb that exploded.
+    start_radius: f32,
+    /// Physics time since the explosion, in seconds.
+    age: f32,
+}
+
+impl RedOrbExplosion {
+    pub fn new(pos: Vec2, radius: f32) -> Self {
+        Self {
+            radius,
+            pos,
+            interactions: 0,
+            start_radius: radius,
+            age: 0.0,
+        }
+    }
 }
 
 #[derive(Message)]
@@ -76,18 +101,6 @@ pub struct RedOrbExplosionEvent {
 #[derive(Component)]
 pub struct PhysicalTimeAnimator;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct RedOrbExplosionLens {
-    radius_start: f32,
-    radius_end: f32,
-}
-
 fn mix(a: f32, b: f32, t: f32) -> f32 {
     a * (1.0 - t) + b * t
 }
-
-impl Lens<RedOrbExplosion> for RedOrbExplosionLens {
-    fn lerp(&mut self, mut target: Mut<RedOrbExplosion>, ratio: f32) {
-        target.radius = mix(self.radius_start, self.radius_end, ratio);
-    }
-}
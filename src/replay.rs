@@ -0,0 +1,286 @@
+//! Recording and replay of runs.
+//!
+//! Every run is recorded as its world seed and the [`TickInput`] of each simulated fixed tick.
+//! When the player dies the recording is saved to [`REPLAY_FILE`], along with the tick and the score
+//! it ended with. Playing it back feeds the recorded input to the simulation instead of the devices,
+//! so the run takes the same course and ends on the same tick with the same [`Score`].
+//! A replay that doesn't is reported as diverged in the log.
+//! The last run can be watched from the death menu, or by starting the game with `--replay`.
+//!
+//! Replays are only expected to match on the build and the machine that recorded them.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Pause,
+    input::{NextTickInput, TickInput, TickInputSystems},
+    persistence,
+    player::{Score, death::player_alive},
+    screens::Screen,
+    space::{WorldSeed, reset_world},
+};
+
+/// The last recorded run, in [`persistence::save_dir`].
+pub const REPLAY_FILE: &str = "replay.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    // `--replay` plays the saved replay back instead of recording the first run
+    let mode = if std::env::args().any(|arg| arg == "--replay") {
+        match Replay::load() {
+            Some(replay) => ReplayMode::Play(replay),
+            None => {
+                warn!("no replay saved in {REPLAY_FILE}");
+                ReplayMode::Record
+            }
+        }
+    } else {
+        ReplayMode::Record
+    };
+
+    app.insert_resource(mode)
+        .init_resource::<RunReplay>()
+        .add_systems(OnEnter(Screen::Gameplay), start_run.before(reset_world))
+        .add_systems(OnEnter(Screen::Dead), finish_run)
+        .add_systems(
+            FixedFirst,
+            record_or_play_tick
+                .after(TickInputSystems::Script)
+                .before(TickInputSystems::Apply)
+                .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false))))
+                .run_if(player_alive),
+        );
+}
+
+/// A recorded run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u32,
+    /// Input of the consecutive ticks, the input held for several ticks is stored once.
+    pub inputs: Vec<InputSpan>,
+    /// How the run ended, `None` until it does.
+    pub outcome: Option<RunOutcome>,
+}
+
+/// The same input held for a number of ticks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputSpan {
+    pub ticks: u32,
+    pub input: TickInput,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunOutcome {
+    /// Simulated ticks, including the one the player died on.
+    pub death_tick: u32,
+    pub score: f32,
+}
+
+impl Replay {
+    pub fn new(seed: WorldSeed) -> Self {
+        Self {
+            seed: seed.0,
+            ..default()
+        }
+    }
+
+    /// The saved replay of the last run, if any.
+    pub fn load() -> Option<Self> {
+        persistence::load(REPLAY_FILE)
+    }
+
+    pub fn save(&self) {
+        persistence::save(REPLAY_FILE, self);
+    }
+
+    /// Appends the input of the next tick.
+    pub fn push(&mut self, input: TickInput) {
+        match self.inputs.last_mut() {
+            Some(span) if span.input == input => span.ticks += 1,
+            _ => self.inputs.push(InputSpan { ticks: 1, input }),
+        }
+    }
+
+    /// Number of recorded ticks.
+    pub fn tick_count(&self) -> u32 {
+        self.inputs.iter().map(|span| span.ticks).sum()
+    }
+
+    /// The input of every recorded tick, in order.
+    pub fn ticks(&self) -> impl Iterator<Item = &TickInput> {
+        self.inputs
+            .iter()
+            .flat_map(|span| std::iter::repeat_n(&span.input, span.ticks as usize))
+    }
+}
+
+/// What the next run does with its input.
+#[derive(Resource, Default, Debug)]
+pub enum ReplayMode {
+    /// Record the input of the devices.
+    #[default]
+    Record,
+    /// Play this replay back, in its world.
+    Play(Replay),
+}
+
+/// Recording of the current run, or the replay it plays back.
+#[derive(Resource, Default, Debug)]
+pub struct RunReplay {
+    pub replay: Replay,
+    /// Ticks simulated since the start of the run.
+    pub tick: u32,
+    /// Input of each tick of the replay, when playing one back.
+    playback: Option<Vec<TickInput>>,
+}
+
+impl RunReplay {
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+}
+
+/// Starts recording, or switches to the world of the replay before it gets generated.
+/// The mode only lasts one run, restarting afterwards records again.
+fn start_run(
+    mut mode: ResMut<ReplayMode>,
+    mut run: ResMut<RunReplay>,
+    mut seed: ResMut<WorldSeed>,
+) {
+    *run = match std::mem::take(&mut *mode) {
+        ReplayMode::Record => RunReplay {
+            replay: Replay::new(*seed),
+            ..default()
+        },
+        ReplayMode::Play(replay) => {
+            info!("playing back a replay of {} ticks", replay.tick_count());
+            *seed = WorldSeed(replay.seed);
+            RunReplay {
+                playback: Some(replay.ticks().cloned().collect()),
+                replay,
+                tick: 0,
+            }
+        }
+    };
+}
+
+/// Records the input of the tick about to be simulated, or replaces it with the recorded one.
+fn record_or_play_tick(mut run: ResMut<RunReplay>, mut next: ResMut<NextTickInput>) {
+    let run = &mut *run;
+    let tick = run.tick as usize;
+    match &run.playback {
+        // past the end of the recording nothing is pressed
+        Some(ticks) => next.0 = ticks.get(tick).cloned().unwrap_or_default(),
+        None => run.replay.push(next.0.clone()),
+    }
+    run.tick += 1;
+}
+
+fn finish_run(mut run: ResMut<RunReplay>, score: Res<Score>) {
+    let outcome = RunOutcome {
+        death_tick: run.tick,
+        score: score.0,
+    };
+
+    if !run.is_playing() {
+        run.replay.outcome = Some(outcome);
+        run.replay.save();
+        return;
+    }
+
+    match run.replay.outcome {
+        Some(expected) if expected == outcome => {
+            info!(
+                "replay matched: died on tick {} with a score of {:.1}",
+                outcome.death_tick, outcome.score
+            );
+        }
+        Some(expected) => warn!(
+            "replay diverged: died on tick {} with a score of {:.1}, recorded tick {} and score {:.1}",
+            outcome.death_tick, outcome.score, expected.death_tick, expected.score
+        ),
+        None => info!(
+            "replay of an unfinished run ended on tick {}",
+            outcome.death_tick
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        asteroids::Asteroid,
+        input::{Action, InjectedActions},
+        player::Player,
+        simulation::Simulation,
+    };
+
+    use super::*;
+
+    const SEED: WorldSeed = WorldSeed(7);
+    /// The recorded run must end by then.
+    const MAX_TICKS: u32 = 600;
+
+    /// Sends a big asteroid at the ship, it flies too fast for the hull to survive the impact.
+    fn spawn_killer_asteroid(sim: &mut Simulation) {
+        let ship = sim
+            .world_mut()
+            .query_filtered::<&Transform, With<Player>>()
+            .single(sim.world())
+            .unwrap()
+            .translation;
+        sim.world_mut().spawn((
+            Asteroid {
+                pos: ship + Vec3::Y * 800.0,
+                radius: 80.0,
+                velocity: Vec2::NEG_Y * 600.0,
+                spin: 0.0,
+            },
+            DespawnOnExit(Screen::Gameplay),
+        ));
+    }
+
+    /// Simulates until the ship dies, with the input of the script.
+    fn fly_until_death(
+        sim: &mut Simulation,
+        mut script: impl FnMut(u32, &mut InjectedActions),
+    ) -> RunOutcome {
+        for _ in 0..MAX_TICKS {
+            sim.run_ticks_with(1, &mut script);
+            if sim.is_dead() {
+                return RunOutcome {
+                    death_tick: sim.world().resource::<RunReplay>().tick,
+                    score: sim.score(),
+                };
+            }
+        }
+        panic!("the ship survived {MAX_TICKS} ticks");
+    }
+
+    #[test]
+    fn replay_ends_like_the_recorded_run() {
+        let mut recording = Simulation::with_plugins(SEED, plugin);
+        spawn_killer_asteroid(&mut recording);
+        // brakes and weaves a little, the asteroid is too big to dodge
+        let recorded = fly_until_death(&mut recording, |tick, input| {
+            if tick % 12 < 4 {
+                input.press(Action::Brake);
+            } else {
+                input.release(Action::Brake);
+            }
+            input.turn = Some(if tick % 16 < 8 { 0.3 } else { -0.3 });
+        });
+        let replay = recording.world().resource::<RunReplay>().replay.clone();
+        assert_eq!(replay.tick_count(), recorded.death_tick);
+
+        let mut playback = Simulation::with_plugins(SEED, move |app: &mut App| {
+            plugin(app);
+            app.insert_resource(ReplayMode::Play(replay));
+        });
+        spawn_killer_asteroid(&mut playback);
+        let replayed = fly_until_death(&mut playback, |_, _| {});
+
+        assert!(playback.world().resource::<RunReplay>().is_playing());
+        assert_eq!(replayed, recorded);
+    }
+}
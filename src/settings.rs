@@ -0,0 +1,109 @@
+//! Player settings: volumes, display options and screen shake.
+//!
+//! They are edited in the settings menu, saved to [`SETTINGS_FILE`] and restored at startup.
+//! The systems here apply the display options whenever [`Settings`] changes,
+//! the volumes are applied by the `audio` module and the shake strength by `vfx`.
+
+use bevy::{post_process::bloom::Bloom, prelude::*, window::PresentMode};
+use bevy_framepace::{FramepaceSettings, Limiter};
+use serde::{Deserialize, Serialize};
+
+use crate::{persistence, vfx};
+
+/// Saved settings, in [`persistence::save_dir`].
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Settings::load()).add_systems(
+        Update,
+        (apply_vsync, apply_framepace, apply_bloom).run_if(resource_changed::<Settings>),
+    );
+}
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+// settings added in a later version get their default value
+#[serde(default)]
+pub struct Settings {
+    /// Linear volume of everything, from 0.0 to 1.0.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    /// Volume of the sound effects, including the UI.
+    pub sfx_volume: f32,
+    pub vsync: bool,
+    /// Limits the frame rate to the refresh rate of the display, lowering the input latency.
+    pub framepace: bool,
+    pub bloom: bool,
+    /// Multiplier of the screen shake, 0.0 disables it.
+    pub screen_shake: f32,
+    /// Race against the best run of the seed.
+    pub ghost: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            vsync: true,
+            framepace: cfg!(feature = "framepace"),
+            bloom: true,
+            screen_shake: 1.0,
+            ghost: true,
+        }
+    }
+}
+
+impl Settings {
+    /// The saved settings, or the default ones if nothing was saved.
+    pub fn load() -> Self {
+        persistence::load(SETTINGS_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        persistence::save(SETTINGS_FILE, self);
+    }
+
+    /// Volume of the music after the master volume.
+    pub fn music_gain(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    /// Volume of the sound effects after the master volume.
+    pub fn sfx_gain(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+}
+
+fn apply_vsync(settings: Res<Settings>, mut window: Single<&mut Window>) {
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
+
+fn apply_framepace(settings: Res<Settings>, mut framepace: ResMut<FramepaceSettings>) {
+    framepace.limiter = if settings.framepace {
+        Limiter::Auto
+    } else {
+        Limiter::Off
+    };
+}
+
+fn apply_bloom(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    cameras: Query<(Entity, Has<Bloom>), With<Camera>>,
+) {
+    for (camera, has_bloom) in &cameras {
+        if settings.bloom && !has_bloom {
+            commands.entity(camera).insert(vfx::BASE_BLOOM);
+        } else if !settings.bloom && has_bloom {
+            commands.entity(camera).remove::<Bloom>();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! The gameplay without the presentation.
+//!
+//! [`plugin`] adds everything a run needs to play out: the physics, the input, the world, the ship
+//! and its hazards. The game adds it along with the window, the renderer and the audio.
+//! A [`Headless`] app adds it alone, e.g. on top of `MinimalPlugins`, to run fixed ticks as fast as
+//! possible with scripted input. The plugins then leave out the meshes, sounds, HUD and camera work,
+//! see [`is_headless`]. [`Simulation`] builds such an app.
+//!
+//! `cargo run -- --headless [--seed 42] [--ticks 3600]` flies a run without touching the controls
+//! and prints how it went, a quick check that the simulation works without the game around it.
+
+use std::time::{Duration, Instant};
+
+use avian2d::prelude::*;
+use bevy::{asset::AssetMetaCheck, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use crate::{
+    PausableSystems, Pause,
+    asset_tracking::{self, ResourceHandles},
+    asteroids, autopilot,
+    cli::{ModeArgs, unknown_option},
+    input::{self, InjectedActions},
+    player::{
+        self, Player, Score,
+        death::{DamageSource, PlayerDamage, player_alive},
+    },
+    red_gas::{self, ExplosionDamage},
+    screens::Screen,
+    space::{self, PopulatedChunks, WorldSeed},
+};
+
+/// How long [`Simulation::new`] waits for the assets, they're read from the disk.
+const ASSET_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((
+        PhysicsPlugins::default().with_length_unit(1.0),
+        input::plugin,
+        autopilot::plugin,
+        asteroids::plugin,
+        player::plugin,
+        space::plugin,
+        red_gas::plugin,
+    ))
+    .insert_resource(Gravity(Vec2::ZERO));
+
+    // Set up the `Pause` state.
+    app.init_state::<Pause>();
+    app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+    app.configure_sets(FixedUpdate, PausableSystems.run_if(in_state(Pause(false))));
+    // the simulation freezes on the death tick, the screen only changes at the end of the frame
+    app.configure_sets(FixedUpdate, PausableSystems.run_if(player_alive));
+}
+
+/// Marks an app simulating the gameplay without window, renderer or audio.
+/// It must be inserted before the plugins are added.
+#[derive(Resource, Default, Debug)]
+pub struct Headless;
+
+/// Whether the plugins are being added to a [`Headless`] app, they skip their presentation then.
+pub fn is_headless(app: &App) -> bool {
+    app.world().contains_resource::<Headless>()
+}
+
+/// A [`Headless`] app playing a run, one fixed tick per update.
+///
+/// The input is scripted through [`InjectedActions`], the state of the run is read back
+/// with the accessors or straight from the [`World`].
+pub struct Simulation {
+    app: App,
+}
+
+impl Simulation {
+    /// Builds the app, loads the assets and starts a run in the world of the seed.
+    /// Panics if the assets can't be loaded, e.g. when not started from the root of the repository.
+    pub fn new(seed: WorldSeed) -> Self {
+        Self::with_plugins(seed, |_: &mut App| {})
+    }
+
+    /// Same as [`Self::new`], with more plugins added to the app before the run starts.
+    pub fn with_plugins<M>(seed: WorldSeed, plugins: impl Plugins<M>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            },
+            StatesPlugin,
+            TransformPlugin,
+            // the debug drawing of the simulation
+            #[cfg(feature = "dev")]
+            bevy::gizmos::GizmoPlugin,
+        ))
+        // the simulation spawns meshes, even if nothing draws them
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_resource::<Headless>()
+        .init_state::<Screen>()
+        .add_plugins((asset_tracking::plugin, plugin))
+        .add_plugins(plugins)
+        // time stands still until the run starts
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+
+        app.finish();
+        app.cleanup();
+
+        let started = Instant::now();
+        while !app.world().resource::<ResourceHandles>().is_all_done() {
+            assert!(
+                started.elapsed() < ASSET_TIMEOUT,
+                "the assets didn't load in {ASSET_TIMEOUT:?}"
+            );
+            app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        // lets the loaded world generation config apply
+        app.update();
+
+        app.insert_resource(seed);
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Gameplay);
+        app.update();
+
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+
+        Self { app }
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// The actions pressed by the script, they stay pressed until released.
+    pub fn input(&mut self) -> Mut<'_, InjectedActions> {
+        self.world_mut().resource_mut::<InjectedActions>()
+    }
+
+    /// Fixed ticks simulated since the run started.
+    pub fn tick(&self) -> u32 {
+        let time = self.world().resource::<Time<Fixed>>();
+        (time.elapsed().as_nanos() / time.timestep().as_nanos()) as u32
+    }
+
+    /// Simulates the ticks with the current input.
+    pub fn run_ticks(&mut self, ticks: u32) {
+        self.run_ticks_with(ticks, |_, _| {});
+    }
+
+    /// Simulates the ticks, the script sets the input of each of them from the tick number.
+    pub fn run_ticks_with(
+        &mut self,
+        ticks: u32,
+        mut script: impl FnMut(u32, &mut InjectedActions),
+    ) {
+        for _ in 0..ticks {
+            let tick = self.tick();
+            script(tick, &mut *self.input());
+            self.app.update();
+        }
+    }
+
+    pub fn score(&self) -> f32 {
+        self.world().resource::<Score>().0
+    }
+
+    pub fn explosion_damage(&self) -> f32 {
+        self.world().resource::<ExplosionDamage>().0
+    }
+
+    pub fn populated_chunks(&self) -> usize {
+        self.world().resource::<PopulatedChunks>().len()
+    }
+
+    /// Where the ship is, `None` once the run is over.
+    pub fn player_position(&mut self) -> Option<Vec2> {
+        self.world_mut()
+            .query_filtered::<&Position, With<Player>>()
+            .single(self.world())
+            .ok()
+            .map(|pos| pos.0)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.killed_by().is_some()
+    }
+
+    /// The hazard that ended the run, `None` while the ship is alive.
+    pub fn killed_by(&self) -> Option<DamageSource> {
+        self.world().resource::<PlayerDamage>().killed_by
+    }
+}
+
+/// Arguments of `--headless`.
+struct HeadlessArgs {
+    seed: WorldSeed,
+    ticks: u32,
+}
+
+impl HeadlessArgs {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            seed: WorldSeed::random(),
+            ticks: 3600,
+        };
+
+        let mut args = ModeArgs::new("--headless", args);
+        while let Some(option) = args.next_option() {
+            match option.as_str() {
+                "--seed" => parsed.seed = WorldSeed(args.value(&option)?),
+                "--ticks" => parsed.ticks = args.value(&option)?,
+                _ => return Err(unknown_option(&option)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Flies a run of `--headless` without touching the controls, then prints where it got.
+/// `args` is the command line without the program name.
+pub fn run(args: impl IntoIterator<Item = String>) -> AppExit {
+    let args = match HeadlessArgs::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("headless run failed: {err}");
+            return AppExit::error();
+        }
+    };
+
+    let mut simulation = Simulation::new(args.seed);
+    simulation.run_ticks(args.ticks);
+
+    let position = simulation
+        .player_position()
+        .map_or("gone".to_string(), |pos| format!("{pos:.0}"));
+    println!(
+        "seed {}: {} ticks, score {:.1}, explosion damage {:.2}, {} chunks populated, ship at {position}{}",
+        args.seed.0,
+        simulation.tick(),
+        simulation.score(),
+        simulation.explosion_damage(),
+        simulation.populated_chunks(),
+        if simulation.is_dead() { ", dead" } else { "" },
+    );
+
+    AppExit::Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asteroids::Asteroid, input::Action};
+
+    /// How a run went, everything a replay relies on.
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        tick: u32,
+        score: f32,
+        position: Option<Vec2>,
+        killed_by: Option<DamageSource>,
+        explosion_damage: f32,
+        populated_chunks: usize,
+        asteroids: usize,
+    }
+
+    fn fly(seed: WorldSeed) -> Outcome {
+        let mut simulation = Simulation::new(seed);
+        simulation.run_ticks_with(900, |tick, input| {
+            input.turn = Some(if tick % 200 < 100 { 0.4 } else { -0.4 });
+            if tick % 90 < 20 {
+                input.press(Action::Brake);
+            } else {
+                input.release(Action::Brake);
+            }
+        });
+
+        let asteroids = simulation
+            .world_mut()
+            .query_filtered::<(), With<Asteroid>>()
+            .iter(simulation.world())
+            .count();
+        Outcome {
+            tick: simulation.tick(),
+            score: simulation.score(),
+            position: simulation.player_position(),
+            killed_by: simulation.killed_by(),
+            explosion_damage: simulation.explosion_damage(),
+            populated_chunks: simulation.populated_chunks(),
+            asteroids,
+        }
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let seed = WorldSeed(42);
+        assert_eq!(fly(seed), fly(seed));
+    }
+}
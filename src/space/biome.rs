@@ -0,0 +1,217 @@
+//! Biomes are large regions of space with their own look and distribution of objects.
+//!
+//! They are selected by a second, low frequency noise layered on top of the gas noise.
+//! Every point in space has a weight for each biome, so the parameters of neighbouring
+//! biomes blend into each other instead of changing abruptly.
+
+use bevy::prelude::*;
+
+use crate::{PausableSystems, player::Player, screens::Screen, simulation::is_headless};
+
+use super::{
+    GasGenerator,
+    config::{BiomeParams, BiomeTable, WorldGenConfig},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Biome>()
+        .init_resource::<CurrentBiome>()
+        .add_systems(
+            FixedUpdate,
+            update_current_biome
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
+
+    if !is_headless(app) {
+        app.add_systems(
+            Update,
+            tint_clear_color
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        )
+        .add_systems(OnExit(Screen::Gameplay), reset_clear_color);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum Biome {
+    /// Gas clouds with a bit of everything.
+    #[default]
+    Nebula,
+    /// Sparse gas and plenty of asteroids.
+    AsteroidBelt,
+    /// Red gas orbs everywhere.
+    RedGasMinefield,
+    /// Almost nothing, you have to get through it on what's left in the tank.
+    Void,
+}
+
+impl Biome {
+    pub const COUNT: usize = 4;
+    pub const ALL: [Biome; Self::COUNT] = [
+        Biome::Nebula,
+        Biome::AsteroidBelt,
+        Biome::RedGasMinefield,
+        Biome::Void,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Nebula => "Nebula",
+            Biome::AsteroidBelt => "Asteroid belt",
+            Biome::RedGasMinefield => "Red gas minefield",
+            Biome::Void => "Void",
+        }
+    }
+
+    /// Position of the biome in the space of the two biome noise channels.
+    fn center(self) -> Vec2 {
+        match self {
+            Biome::Nebula => Vec2::new(0.2, 0.2),
+            Biome::AsteroidBelt => Vec2::new(-0.2, 0.2),
+            Biome::RedGasMinefield => Vec2::new(0.2, -0.2),
+            Biome::Void => Vec2::new(-0.2, -0.2),
+        }
+    }
+}
+
+/// Biome a gas orb was spawned in, it decides the material of the orb.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct OrbBiome(pub Biome);
+
+/// How much each biome contributes at some point in space. The weights sum up to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiomeWeights(pub [f32; Biome::COUNT]);
+
+impl Default for BiomeWeights {
+    fn default() -> Self {
+        let mut weights = [0.0; Biome::COUNT];
+        weights[Biome::default() as usize] = 1.0;
+        Self(weights)
+    }
+}
+
+/// Controls how wide the transitions between biomes are.
+const BLEND_SHARPNESS: f32 = 60.0;
+
+impl BiomeWeights {
+    /// Weights of the biomes for a sample of the two biome noise channels.
+    pub fn from_sample(sample: Vec2) -> Self {
+        let mut weights = Biome::ALL.map(|biome| {
+            let d = sample.distance_squared(biome.center());
+            (-d * BLEND_SHARPNESS).exp()
+        });
+
+        let total: f32 = weights.iter().sum();
+        for w in &mut weights {
+            *w /= total;
+        }
+
+        Self(weights)
+    }
+
+    /// Linear interpolation between two sets of weights.
+    pub fn mix(&self, other: &Self, t: f32) -> Self {
+        let mut weights = self.0;
+        for (w, o) in weights.iter_mut().zip(other.0) {
+            *w = w.lerp(o, t);
+        }
+        Self(weights)
+    }
+
+    pub fn get(&self, biome: Biome) -> f32 {
+        self.0[biome as usize]
+    }
+
+    pub fn dominant(&self) -> Biome {
+        Biome::ALL
+            .into_iter()
+            .max_by(|a, b| self.get(*a).total_cmp(&self.get(*b)))
+            .unwrap_or_default()
+    }
+
+    /// Picks a biome with probability proportional to its weight,
+    /// `t` is a random number in [0, 1) range.
+    /// This is used to dither discrete choices (like orb materials) across a transition.
+    pub fn pick(&self, t: f32) -> Biome {
+        let mut acc = 0.0;
+        for biome in Biome::ALL {
+            acc += self.get(biome);
+            if t < acc {
+                return biome;
+            }
+        }
+        self.dominant()
+    }
+
+    /// Parameters of the biomes blended by their weights.
+    pub fn params(&self, table: &BiomeTable) -> BiomeParams {
+        let mut params = BiomeParams {
+            orb_threshold: 0.0,
+            red_orb_chance: 0.0,
+            asteroid_chance: 0.0,
+            clear_color: [0.0; 3],
+        };
+
+        for biome in Biome::ALL {
+            let w = self.get(biome);
+            let p = table.get(biome);
+            params.orb_threshold += p.orb_threshold * w;
+            params.red_orb_chance += p.red_orb_chance * w;
+            params.asteroid_chance += p.asteroid_chance * w;
+            for (c, pc) in params.clear_color.iter_mut().zip(p.clear_color) {
+                *c += pc * w;
+            }
+        }
+
+        params
+    }
+}
+
+/// Biome the player is currently in. Other systems (HUD, music, etc.) can read this.
+#[derive(Resource, Default, Debug)]
+pub struct CurrentBiome {
+    /// The biome with the highest weight at the player position.
+    pub biome: Biome,
+    pub weights: BiomeWeights,
+}
+
+fn update_current_biome(
+    gas: Res<GasGenerator>,
+    player: Single<&Transform, With<Player>>,
+    mut current: ResMut<CurrentBiome>,
+) {
+    let weights = gas.biome_weights(player.translation.truncate());
+    let biome = weights.dominant();
+
+    if biome != current.biome {
+        info!("entering biome: {}", biome.name());
+    }
+
+    current.biome = biome;
+    current.weights = weights;
+}
+
+fn tint_clear_color(
+    current: Res<CurrentBiome>,
+    config: Res<WorldGenConfig>,
+    mut clear_color: ResMut<ClearColor>,
+    time: Res<Time>,
+) {
+    let [r, g, b] = current.weights.params(&config.biomes).clear_color;
+    let target = Color::srgb(r, g, b);
+    clear_color.0 = clear_color
+        .0
+        .mix(&target, (2.0 * time.delta_secs()).min(1.0));
+}
+
+fn reset_clear_color(
+    config: Res<WorldGenConfig>,
+    mut clear_color: ResMut<ClearColor>,
+    mut current: ResMut<CurrentBiome>,
+) {
+    *current = CurrentBiome::default();
+    let [r, g, b] = config.biomes.get(Biome::default()).clear_color;
+    clear_color.0 = Color::srgb(r, g, b);
+}
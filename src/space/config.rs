@@ -0,0 +1,436 @@
+//! World generation parameters, loaded from a RON asset so the galaxy can be tuned without recompiling.
+//!
+//! The config is hot-reloaded in `dev_native` builds: every loaded chunk is regenerated with the new values.
+//! A config with invalid values fails to load with an error describing the problem,
+//! in which case the previous (or the default) config stays in use.
+
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::asset_tracking::LoadResource;
+
+use super::{GasGenerator, PopulatedChunks, WorldSeed, biome::Biome, delta::ChunkDeltas};
+
+/// Path of the config, relative to the assets folder.
+pub const CONFIG_PATH: &str = "config/world.worldgen.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<WorldGenConfig>()
+        .init_asset_loader::<WorldGenConfigLoader>()
+        .init_resource::<WorldGenConfig>()
+        .register_type::<WorldGenAssets>()
+        .load_resource::<WorldGenAssets>()
+        .add_systems(Update, apply_world_gen_config);
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct WorldGenAssets {
+    #[dependency]
+    pub config: Handle<WorldGenConfig>,
+}
+
+impl FromWorld for WorldGenAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            config: assets.load(CONFIG_PATH),
+        }
+    }
+}
+
+/// All the world generation parameters.
+/// The active config is available as a resource, the default values are used until the asset is loaded.
+#[derive(Resource, Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct WorldGenConfig {
+    /// Width of a chunk in world units.
+    pub chunk_size: f32,
+    /// Number of orbs per m²
+    pub max_cloud_density: f32,
+    /// Number of chunks loaded around the player along each axis.
+    pub render_distance: i32,
+    /// Chunks closer than this (in chunks) have a separate entity for every gas orb, so the gas can be ignited.
+    /// Further chunks draw all their orbs as a single mesh.
+    /// Flames spread about 400 units at most, so keep at least one chunk of margin.
+    pub detail_distance: i32,
+    /// Frequency of the gas noise.
+    pub noise_frequency: f32,
+    /// Frequency of the noise selecting biomes, should be much lower than `noise_frequency`.
+    pub biome_noise_frequency: f32,
+    pub orbs: OrbConfig,
+    pub red_orbs: ScatterConfig,
+    pub asteroids: ScatterConfig,
+    pub gravity: GravityConfig,
+    pub biomes: BiomeTable,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct OrbConfig {
+    pub min_size: f32,
+    /// Size added to `min_size` per unit of orb mass.
+    pub scale: f32,
+    /// Depth of the cloud, scaled by the orb mass.
+    pub cloud_z_scale: f32,
+}
+
+/// Size and depth of objects sparsely scattered across space.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ScatterConfig {
+    pub min_size: f32,
+    pub size_variation: f32,
+    pub cloud_z_scale: f32,
+}
+
+/// Gravity wells and black holes, see [`super::gravity`].
+#[derive(Deserialize, Clone, Debug)]
+pub struct GravityConfig {
+    /// Chance for a chunk outside the intro region to contain a gravity body.
+    pub chance: f32,
+    /// Chance for a gravity body to be a black hole rather than a gravity well.
+    pub black_hole_chance: f32,
+    /// Acceleration at the center of the weakest bodies.
+    pub min_strength: f32,
+    pub strength_variation: f32,
+    /// Distance the pull reaches.
+    pub influence_radius: f32,
+    /// Black holes swallow the ship closer than this.
+    pub horizon_radius: f32,
+    /// Radius in which black holes swirl the generated gas, at most `3/8 * chunk_size`
+    /// so the swirl stays within the chunk of the black hole.
+    pub lens_radius: f32,
+    /// Angle (in radians) the gas right next to the horizon is twisted by.
+    pub lens_twist: f32,
+}
+
+/// Generation parameters supplied by a biome.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct BiomeParams {
+    /// Gas noise value above which gas orbs spawn.
+    pub orb_threshold: f32,
+    /// Chance for a red orb to spawn in a cell suitable for it.
+    pub red_orb_chance: f32,
+    /// Chance for an asteroid to spawn in a cell suitable for it.
+    pub asteroid_chance: f32,
+    /// sRGB tint of the background.
+    pub clear_color: [f32; 3],
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BiomeTable {
+    pub nebula: BiomeParams,
+    pub asteroid_belt: BiomeParams,
+    pub red_gas_minefield: BiomeParams,
+    pub void: BiomeParams,
+}
+
+impl BiomeTable {
+    pub fn get(&self, biome: Biome) -> &BiomeParams {
+        match biome {
+            Biome::Nebula => &self.nebula,
+            Biome::AsteroidBelt => &self.asteroid_belt,
+            Biome::RedGasMinefield => &self.red_gas_minefield,
+            Biome::Void => &self.void,
+        }
+    }
+
+    pub fn get_mut(&mut self, biome: Biome) -> &mut BiomeParams {
+        match biome {
+            Biome::Nebula => &mut self.nebula,
+            Biome::AsteroidBelt => &mut self.asteroid_belt,
+            Biome::RedGasMinefield => &mut self.red_gas_minefield,
+            Biome::Void => &mut self.void,
+        }
+    }
+}
+
+/// The config shipped in the assets. Built in as the defaults, so the two can't drift apart.
+const SHIPPED_CONFIG: &[u8] = include_bytes!("../../assets/config/world.worldgen.ron");
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self::from_ron(SHIPPED_CONFIG).expect("the shipped world generation config is valid")
+    }
+}
+
+impl WorldGenConfig {
+    /// Parses and validates a config in the RON format.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, WorldGenConfigError> {
+        let config: WorldGenConfig = ron::de::from_bytes(bytes)?;
+        config.validate().map_err(WorldGenConfigError::Invalid)?;
+        Ok(config)
+    }
+
+    /// Generates nothing, for hand-built scenes. The gas noise still steers the ship.
+    pub fn empty() -> Self {
+        let mut config = Self::default();
+        for biome in Biome::ALL {
+            let params = config.biomes.get_mut(biome);
+            // the noise never gets this high
+            params.orb_threshold = 2.0;
+            params.red_orb_chance = 0.0;
+            params.asteroid_chance = 0.0;
+        }
+        config.gravity.chance = 0.0;
+        config
+    }
+
+    /// Number of subdivisions along each axis required to get the desired maximum cloud density.
+    pub fn chunk_subdiv(&self) -> usize {
+        ((self.max_cloud_density * self.chunk_size * self.chunk_size) as usize).isqrt()
+    }
+
+    /// Checks that the values make sense, so a typo can't freeze or crash the game.
+    pub fn validate(&self) -> Result<(), String> {
+        fn positive(name: &str, value: f32) -> Result<(), String> {
+            if value.is_finite() && value > 0.0 {
+                Ok(())
+            } else {
+                Err(format!("`{name}` must be a positive number, got {value}"))
+            }
+        }
+
+        fn non_negative(name: &str, value: f32) -> Result<(), String> {
+            if value.is_finite() && value >= 0.0 {
+                Ok(())
+            } else {
+                Err(format!("`{name}` must not be negative, got {value}"))
+            }
+        }
+
+        fn unit(name: &str, value: f32) -> Result<(), String> {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("`{name}` must be between 0 and 1, got {value}"))
+            }
+        }
+
+        positive("chunk_size", self.chunk_size)?;
+        positive("max_cloud_density", self.max_cloud_density)?;
+        positive("noise_frequency", self.noise_frequency)?;
+        positive("biome_noise_frequency", self.biome_noise_frequency)?;
+
+        if !(1..=8).contains(&self.render_distance) {
+            return Err(format!(
+                "`render_distance` must be between 1 and 8, got {}",
+                self.render_distance
+            ));
+        }
+
+        if !(1..=self.render_distance).contains(&self.detail_distance) {
+            return Err(format!(
+                "`detail_distance` must be between 1 and `render_distance` ({}), got {}",
+                self.render_distance, self.detail_distance
+            ));
+        }
+
+        // too many cells per chunk would stall generation for seconds
+        let subdiv = self.chunk_subdiv();
+        if !(1..=256).contains(&subdiv) {
+            return Err(format!(
+                "`max_cloud_density * chunk_size²` gives {subdiv} cells along each axis of a chunk, must be between 1 and 256"
+            ));
+        }
+
+        non_negative("orbs.min_size", self.orbs.min_size)?;
+        non_negative("orbs.scale", self.orbs.scale)?;
+        non_negative("orbs.cloud_z_scale", self.orbs.cloud_z_scale)?;
+
+        for (name, scatter) in [("red_orbs", &self.red_orbs), ("asteroids", &self.asteroids)] {
+            positive(&format!("{name}.min_size"), scatter.min_size)?;
+            non_negative(&format!("{name}.size_variation"), scatter.size_variation)?;
+            non_negative(&format!("{name}.cloud_z_scale"), scatter.cloud_z_scale)?;
+        }
+
+        let gravity = &self.gravity;
+        unit("gravity.chance", gravity.chance)?;
+        unit("gravity.black_hole_chance", gravity.black_hole_chance)?;
+        non_negative("gravity.min_strength", gravity.min_strength)?;
+        non_negative("gravity.strength_variation", gravity.strength_variation)?;
+        positive("gravity.horizon_radius", gravity.horizon_radius)?;
+        positive("gravity.influence_radius", gravity.influence_radius)?;
+        positive("gravity.lens_radius", gravity.lens_radius)?;
+        if !gravity.lens_twist.is_finite() {
+            return Err("`gravity.lens_twist` must be a number".to_string());
+        }
+        if gravity.influence_radius <= gravity.horizon_radius {
+            return Err(format!(
+                "`gravity.influence_radius` ({}) must be larger than `gravity.horizon_radius` ({})",
+                gravity.influence_radius, gravity.horizon_radius
+            ));
+        }
+        if gravity.lens_radius > self.chunk_size * 3.0 / 8.0 {
+            return Err(format!(
+                "`gravity.lens_radius` must be at most 3/8 of `chunk_size` ({}), got {}",
+                self.chunk_size * 3.0 / 8.0,
+                gravity.lens_radius
+            ));
+        }
+
+        for biome in Biome::ALL {
+            let params = self.biomes.get(biome);
+            let name = biome.name();
+            if !params.orb_threshold.is_finite() {
+                return Err(format!("`orb_threshold` of {name} must be a number"));
+            }
+            unit(&format!("red_orb_chance of {name}"), params.red_orb_chance)?;
+            unit(
+                &format!("asteroid_chance of {name}"),
+                params.asteroid_chance,
+            )?;
+            for c in params.clear_color {
+                unit(&format!("clear_color of {name}"), c)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum WorldGenConfigError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for WorldGenConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read world generation config: {err}"),
+            Self::Ron(err) => write!(f, "could not parse world generation config: {err}"),
+            Self::Invalid(err) => write!(f, "invalid world generation config: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for WorldGenConfigError {}
+
+impl From<std::io::Error> for WorldGenConfigError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for WorldGenConfigError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Ron(err)
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct WorldGenConfigLoader;
+
+impl AssetLoader for WorldGenConfigLoader {
+    type Asset = WorldGenConfig;
+    type Settings = ();
+    type Error = WorldGenConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        WorldGenConfig::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["worldgen.ron"]
+    }
+}
+
+/// Makes a freshly loaded (or hot-reloaded) config active and regenerates the loaded chunks.
+fn apply_world_gen_config(
+    mut cmds: Commands,
+    mut events: MessageReader<AssetEvent<WorldGenConfig>>,
+    configs: Res<Assets<WorldGenConfig>>,
+    mut config: ResMut<WorldGenConfig>,
+    seed: Res<WorldSeed>,
+    mut gas: ResMut<GasGenerator>,
+    mut populated: ResMut<PopulatedChunks>,
+    mut deltas: ResMut<ChunkDeltas>,
+) {
+    let mut changed = false;
+
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && let Some(new_config) = configs.get(*id)
+        {
+            *config = new_config.clone();
+            changed = true;
+        }
+    }
+
+    if !changed {
+        return;
+    }
+
+    info!("world generation config applied");
+
+    *gas = GasGenerator::new(*seed, &config);
+    for (_, chunk_entity) in populated.0.drain() {
+        cmds.entity(chunk_entity).try_despawn();
+    }
+    // the regenerated chunks have different content, the recorded indices don't apply anymore
+    deltas.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_config_is_valid() {
+        if let Err(err) = WorldGenConfig::from_ron(SHIPPED_CONFIG) {
+            panic!("{err}");
+        }
+        assert!(WorldGenConfig::empty().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_non_positive_values() {
+        let fields: [fn(&mut WorldGenConfig) -> &mut f32; 4] = [
+            |config| &mut config.chunk_size,
+            |config| &mut config.noise_frequency,
+            |config| &mut config.asteroids.min_size,
+            |config| &mut config.gravity.horizon_radius,
+        ];
+        for field in fields {
+            for value in [0.0, -1.0] {
+                let mut config = WorldGenConfig::default();
+                *field(&mut config) = value;
+                assert!(config.validate().is_err(), "{value} was accepted");
+            }
+        }
+
+        let mut config = WorldGenConfig::default();
+        config.orbs.scale = -1.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_nan() {
+        let fields: [fn(&mut WorldGenConfig) -> &mut f32; 6] = [
+            |config| &mut config.chunk_size,
+            |config| &mut config.orbs.scale,
+            |config| &mut config.gravity.chance,
+            |config| &mut config.gravity.lens_twist,
+            |config| &mut config.biomes.void.orb_threshold,
+            |config| &mut config.biomes.nebula.clear_color[1],
+        ];
+        for (i, field) in fields.into_iter().enumerate() {
+            let mut config = WorldGenConfig::default();
+            *field(&mut config) = f32::NAN;
+            assert!(config.validate().is_err(), "NaN was accepted in field {i}");
+        }
+    }
+}
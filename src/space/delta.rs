@@ -0,0 +1,159 @@
+//! Memory of what the player destroyed in each chunk.
+//!
+//! Chunk content is generated deterministically, so an object is identified by its chunk and
+//! its index in the generated [`ChunkContent`](super::generation::ChunkContent).
+//! When a chunk is populated again, the objects recorded here are left out.
+
+use avian2d::parry::utils::hashmap::HashMap;
+use bevy::prelude::*;
+
+use super::gas::BurningGasOrb;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChunkDeltas>()
+        .add_observer(record_burnt_orb);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkItemKind {
+    GasOrb,
+    RedGasOrb,
+    Asteroid,
+}
+
+/// Marks an object spawned as part of a chunk's generated content.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ChunkItem {
+    pub chunk_coords: IVec2,
+    pub kind: ChunkItemKind,
+    /// Index of the object among the objects of the same kind in the chunk.
+    pub index: u32,
+}
+
+/// A growable set of small indices, one bit per index.
+#[derive(Default, Clone, Debug)]
+pub struct IndexSet(Vec<u64>);
+
+impl IndexSet {
+    pub fn insert(&mut self, index: u32) {
+        let (word, bit) = (index as usize / 64, index % 64);
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        self.0[word] |= 1 << bit;
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        let (word, bit) = (index as usize / 64, index % 64);
+        self.0.get(word).is_some_and(|w| w & (1 << bit) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|w| *w == 0)
+    }
+}
+
+/// Changes made to a single chunk since it was first generated.
+#[derive(Default, Clone, Debug)]
+pub struct ChunkDelta {
+    pub consumed_orbs: IndexSet,
+    pub exploded_red_orbs: IndexSet,
+    pub destroyed_asteroids: IndexSet,
+}
+
+impl ChunkDelta {
+    fn set(&self, kind: ChunkItemKind) -> &IndexSet {
+        match kind {
+            ChunkItemKind::GasOrb => &self.consumed_orbs,
+            ChunkItemKind::RedGasOrb => &self.exploded_red_orbs,
+            ChunkItemKind::Asteroid => &self.destroyed_asteroids,
+        }
+    }
+
+    fn set_mut(&mut self, kind: ChunkItemKind) -> &mut IndexSet {
+        match kind {
+            ChunkItemKind::GasOrb => &mut self.consumed_orbs,
+            ChunkItemKind::RedGasOrb => &mut self.exploded_red_orbs,
+            ChunkItemKind::Asteroid => &mut self.destroyed_asteroids,
+        }
+    }
+
+    /// Whether the object was removed from the chunk and shouldn't be spawned again.
+    pub fn is_removed(&self, kind: ChunkItemKind, index: u32) -> bool {
+        self.set(kind).contains(index)
+    }
+}
+
+/// Deltas of every chunk changed during the current run.
+#[derive(Resource, Default, Debug)]
+pub struct ChunkDeltas(pub HashMap<IVec2, ChunkDelta>);
+
+impl ChunkDeltas {
+    /// Remembers that the object is gone for good.
+    pub fn record(&mut self, item: &ChunkItem) {
+        self.0
+            .entry(item.chunk_coords)
+            .or_default()
+            .set_mut(item.kind)
+            .insert(item.index);
+    }
+
+    pub fn get(&self, chunk_coords: IVec2) -> Option<&ChunkDelta> {
+        self.0.get(&chunk_coords)
+    }
+}
+
+/// A burning orb is as good as gone, it won't be there when the player comes back.
+fn record_burnt_orb(
+    trigger: On<Add, BurningGasOrb>,
+    q_items: Query<&ChunkItem>,
+    mut deltas: ResMut<ChunkDeltas>,
+) {
+    if let Ok(item) = q_items.get(trigger.event().event_target()) {
+        deltas.record(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_set_spans_words() {
+        let mut set = IndexSet::default();
+        assert!(set.is_empty());
+        for index in [0, 63, 64, 127, 200] {
+            set.insert(index);
+        }
+        set.insert(64);
+
+        assert_eq!(set.len(), 5);
+        assert!(!set.is_empty());
+        for index in [0, 63, 64, 127, 200] {
+            assert!(set.contains(index), "{index} is missing");
+        }
+        for index in [1, 62, 65, 128, 199, 201, 10_000] {
+            assert!(!set.contains(index), "{index} was never inserted");
+        }
+    }
+
+    #[test]
+    fn deltas_are_kept_per_chunk_and_kind() {
+        let mut deltas = ChunkDeltas::default();
+        deltas.record(&ChunkItem {
+            chunk_coords: IVec2::new(1, -2),
+            kind: ChunkItemKind::RedGasOrb,
+            index: 70,
+        });
+
+        let delta = deltas.get(IVec2::new(1, -2)).unwrap();
+        assert!(delta.is_removed(ChunkItemKind::RedGasOrb, 70));
+        assert!(!delta.is_removed(ChunkItemKind::Asteroid, 70));
+        assert!(!delta.is_removed(ChunkItemKind::RedGasOrb, 6));
+        assert!(deltas.get(IVec2::new(-2, 1)).is_none());
+    }
+}
@@ -1,13 +1,19 @@
 use bevy::{
     color::palettes::{
-        css::{GOLD, RED, WHEAT, WHITE},
+        css::{
+            DEEP_SKY_BLUE, GOLD, LIGHT_CYAN, LIGHT_STEEL_BLUE, MEDIUM_ORCHID, RED, SALMON, WHEAT,
+            WHITE, YELLOW,
+        },
         tailwind::GRAY_700,
     },
     mesh::CircleMeshBuilder,
     prelude::*, // render::mesh::CircleMeshBuilder,
 };
 
-use crate::asset_tracking::LoadResource;
+use crate::{
+    asset_tracking::LoadResource,
+    space::{biome::Biome, gas::kind::GasKind},
+};
 
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
@@ -16,6 +22,27 @@ pub struct OrbAssets {
     pub orb_mesh: Handle<Mesh>,
     #[dependency]
     pub orb_materials: Vec<Handle<StandardMaterial>>,
+    /// Materials of unburnt gas orbs, indexed by [`Biome`].
+    #[dependency]
+    pub biome_orb_materials: Vec<Handle<StandardMaterial>>,
+    /// Materials of unburnt special gas orbs, indexed by [`GasKind`].
+    /// Standard gas uses the material of its biome instead.
+    #[dependency]
+    pub kind_orb_materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl OrbAssets {
+    pub fn biome_orb_material(&self, biome: Biome) -> Handle<StandardMaterial> {
+        self.biome_orb_materials[biome as usize].clone()
+    }
+
+    /// Material of an unburnt orb.
+    pub fn orb_material(&self, biome: Biome, kind: GasKind) -> Handle<StandardMaterial> {
+        match kind {
+            GasKind::Standard => self.biome_orb_material(biome),
+            _ => self.kind_orb_materials[kind as usize].clone(),
+        }
+    }
 }
 
 impl FromWorld for OrbAssets {
@@ -54,9 +81,52 @@ impl FromWorld for OrbAssets {
         //     ..Default::default()
         // }));
 
+        let biome_orb_materials = Biome::ALL
+            .map(|biome| match biome {
+                Biome::Nebula => orb_materials[0].clone(),
+                Biome::AsteroidBelt => assets.add(StandardMaterial {
+                    base_color: LIGHT_STEEL_BLUE.with_alpha(0.5).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    emissive: (LIGHT_STEEL_BLUE * 0.8).into(),
+                    ..Default::default()
+                }),
+                Biome::RedGasMinefield => assets.add(StandardMaterial {
+                    base_color: SALMON.with_alpha(0.5).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    emissive: (SALMON * 1.0).into(),
+                    ..Default::default()
+                }),
+                Biome::Void => assets.add(StandardMaterial {
+                    base_color: LIGHT_CYAN.with_alpha(0.4).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    emissive: (LIGHT_CYAN * 0.6).into(),
+                    ..Default::default()
+                }),
+            })
+            .to_vec();
+
+        let kind_orb_materials = GasKind::ALL
+            .map(|kind| {
+                let color = match kind {
+                    GasKind::Standard => return orb_materials[0].clone(),
+                    GasKind::Volatile => YELLOW,
+                    GasKind::Coolant => DEEP_SKY_BLUE,
+                    GasKind::Ionized => MEDIUM_ORCHID,
+                };
+                assets.add(StandardMaterial {
+                    base_color: color.with_alpha(0.6).into(),
+                    alpha_mode: AlphaMode::Blend,
+                    emissive: (color * 1.5).into(),
+                    ..Default::default()
+                })
+            })
+            .to_vec();
+
         Self {
             orb_mesh,
             orb_materials,
+            biome_orb_materials,
+            kind_orb_materials,
         }
     }
 }
@@ -1,5 +1,3 @@
-use std::collections::VecDeque;
-
 use avian2d::prelude::Physics;
 use bevy::{
     app::{App, Update},
@@ -12,15 +10,25 @@ use bevy_spatial::{SpatialAccess, kdtree::KDTree2};
 
 use crate::{
     PausableSystems,
+    player::Player,
     red_gas::{RedGasOrb, RedOrbExplosionEvent},
     screens::Screen,
-    space::gas::{BurningGasOrb, assets::OrbAssets, ignite_gas},
+    space::gas::{
+        BurningGasOrb, HeatDamage,
+        assets::OrbAssets,
+        fire::{FireField, Fuel},
+        ignite_gas,
+        kind::GasKind,
+    },
 };
 
 use super::GasOrb;
 
 pub fn plugin(app: &mut App) {
     app.add_message::<BurnEvent>()
+        .init_resource::<FireField>()
+        .init_resource::<HeatDamage>()
+        .add_systems(OnEnter(Screen::Gameplay), reset_fire)
         .configure_sets(
             FixedUpdate,
             UpdateGasSet
@@ -30,16 +38,18 @@ pub fn plugin(app: &mut App) {
         )
         .add_systems(
             FixedUpdate,
-            (propagate_flames, update_burning_orbs).in_set(UpdateGasSet),
+            (propagate_flames, update_burning_orbs, update_heat_damage).in_set(UpdateGasSet),
         );
 }
 
-const BURN_TIME: u32 = 300;
-const CELL_SIZE: f32 = 16.;
-const MAX_COUNT: u32 = 1000;
-const LIFETIME: u32 = 30;
-const SLOWDOWN: u32 = 10;
-const BASE_DELAY: u32 = 15;
+/// Heat gained per second while touching gas in its hot phase.
+const HEAT_RATE: f32 = 1.5;
+/// Heat lost per second away from the flames.
+const COOLING_RATE: f32 = 0.5;
+/// Distance from the center of the ship at which the flames start to hurt.
+const SHIP_HEAT_RADIUS: f32 = 3.0;
+/// Flames are looked for this far around the ship, well beyond the size of an orb in its hot phase.
+const FLAME_SEARCH_RADIUS: f32 = 40.0;
 
 #[derive(SystemSet, Hash, Debug, Eq, PartialEq, Clone)]
 pub struct UpdateGasSet;
@@ -49,10 +59,35 @@ pub struct BurnEvent {
     pub pos: Vec2,
 }
 
-pub struct OrbExplosionCell {
-    pos: Vec2,
-    time: u32,
-    life: u32,
+/// The gas orbs as fuel for the fire, each kind of gas burns differently.
+struct GasFuel<'a, 'w, 's> {
+    tree: &'a KDTree2<GasOrb>,
+    kinds: &'a Query<'w, 's, &'static GasKind>,
+}
+
+impl Fuel for GasFuel<'_, '_, '_> {
+    fn for_each_within(&self, pos: Vec2, radius: f32, mut f: impl FnMut(Entity, f32)) {
+        for (_, entity) in self.tree.within_distance(pos, radius) {
+            if let Some(e) = entity {
+                let kind = self.kinds.get(e).copied().unwrap_or_default();
+                f(e, kind.params().flammability);
+            }
+        }
+    }
+}
+
+impl Fuel for KDTree2<RedGasOrb> {
+    fn for_each_within(&self, pos: Vec2, radius: f32, mut f: impl FnMut(Entity, f32)) {
+        for (_, entity) in self.within_distance(pos, radius) {
+            if let Some(e) = entity {
+                f(e, 1.0);
+            }
+        }
+    }
+}
+
+fn reset_fire(mut fire: ResMut<FireField>) {
+    fire.clear();
 }
 
 pub fn propagate_flames(
@@ -60,107 +95,47 @@ pub fn propagate_flames(
     mut commands: Commands,
     orb_tree: Res<KDTree2<GasOrb>>,
     red_orb_tree: Res<KDTree2<RedGasOrb>>,
+    q_kinds: Query<&GasKind>,
 
     mut red_orb_explosion_events: MessageWriter<RedOrbExplosionEvent>,
 
     time: Res<Time<Physics>>,
-    mut explosion_queue: Local<VecDeque<OrbExplosionCell>>,
+    mut fire: ResMut<FireField>,
 
     #[cfg(feature = "dev")] mut gizmos: Gizmos,
 ) {
     let curr_time = time.elapsed().as_millis() as u32;
 
     for event in burn.read() {
-        explosion_queue.push_back(OrbExplosionCell {
-            pos: event.pos,
-            time: curr_time,
-            life: LIFETIME,
-        });
+        fire.ignite(event.pos, curr_time);
     }
 
-    debug!("explosions: {}", explosion_queue.len());
+    debug!("fire fronts: {}", fire.len());
 
-    let mut new_explosions = Vec::<OrbExplosionCell>::new();
-
-    let mut i = 0;
-    let variation = [0.3, 0.7, 0.4, 0.6, 0.8]; // in total 2.8, 0.5 avg
-
-    explosion_queue.retain(|explosion| {
-        if explosion.life == 0 {
-            return false;
-        }
-
-        let size = CELL_SIZE * variation[i % variation.len()];
-        i += 1;
-
-        #[cfg(feature = "dev")]
+    #[cfg(feature = "dev")]
+    for front in fire.fronts() {
         gizmos.circle_2d(
-            Isometry2d::from_translation(explosion.pos),
-            size,
+            Isometry2d::from_translation(front.pos),
+            front.size(),
             LIGHT_GREEN,
         );
+    }
 
-        // condition of burn propagation. basically the older the explosion is the longer it takes to propagate
-        if curr_time > explosion.time + BASE_DELAY + SLOWDOWN * (LIFETIME - explosion.life) {
-            let mut burnt_orbs = false;
-            for (_, entity) in orb_tree.within_distance(explosion.pos, size / 2.0) {
-                if let Some(e) = entity {
-                    commands
-                        .entity(e)
-                        .try_remove::<GasOrb>()
-                        .try_insert(BurningGasOrb(curr_time));
-                    burnt_orbs = true;
-                }
-            }
-            for (_, entity) in red_orb_tree.within_distance(explosion.pos, size / 2.0) {
-                if let Some(e) = entity {
-                    red_orb_explosion_events.write(RedOrbExplosionEvent { entity: e, meta: 0 });
-                    // commands
-                    //     .entity(e)
-                    //     .try_remove::<GasOrb>()
-                    //     .try_insert(BurningGasOrb(curr_time));
-                    // burnt_orbs = true;
-                }
-            }
-
-            if !burnt_orbs {
-                return false;
-            }
-
-            let neis = [
-                explosion.pos + Vec2::Y * size,
-                explosion.pos - Vec2::Y * size,
-                explosion.pos + Vec2::X * size,
-                explosion.pos - Vec2::X * size,
-            ];
-
-            for nei in neis {
-                let life = explosion.life - 1;
-
-                if life > 0 {
-                    new_explosions.push(OrbExplosionCell {
-                        pos: nei,
-                        time: curr_time,
-                        life,
-                    });
-                }
-            }
-            false
-        } else {
-            true
-        }
-    });
-
-    let new_len = new_explosions.len();
-    let free_space = MAX_COUNT.saturating_sub(explosion_queue.len() as u32);
-
-    for explosion in new_explosions {
-        i += 1;
+    let gas_fuel = GasFuel {
+        tree: &orb_tree,
+        kinds: &q_kinds,
+    };
+    let step = fire.step(curr_time, &gas_fuel, &*red_orb_tree);
+
+    for e in step.ignited {
+        commands
+            .entity(e)
+            .try_remove::<GasOrb>()
+            .try_insert(BurningGasOrb(curr_time));
+    }
 
-        // probabilistically add some explosions to the queue
-        if (variation[i % variation.len()] * new_len as f32) < free_space as f32 {
-            explosion_queue.push_back(explosion);
-        }
+    for e in step.red_orbs {
+        red_orb_explosion_events.write(RedOrbExplosionEvent { entity: e, meta: 0 });
     }
 }
 
@@ -169,6 +144,7 @@ fn update_burning_orbs(
         &mut Transform,
         &mut MeshMaterial3d<StandardMaterial>,
         &BurningGasOrb,
+        Option<&GasKind>,
     )>,
     orb_assets: Res<OrbAssets>,
     time: Res<Time<Physics>>,
@@ -176,21 +152,61 @@ fn update_burning_orbs(
     let ct = time.elapsed().as_millis() as u32;
     let delta = time.delta_secs();
 
-    orb_q.par_iter_mut().for_each(|(mut tr, mut mat, time)| {
-        let dt = ct - time.0;
-
-        /*         if dt > 2 * BURN_TIME {
-            mat.0 = orb_assets.orb_materials[3].clone();
-        } else  */
-        if dt > BURN_TIME {
-            mat.0 = orb_assets.orb_materials[2].clone();
-            tr.scale = tr.scale.lerp(tr.scale * 0.996, 60.0 * delta);
-        } else {
-            mat.0 = orb_assets.orb_materials[1].clone();
-            tr.scale = tr
-                .scale
-                .lerp(tr.scale * 1.013, 60.0 * delta)
-                .min(Vec3::splat(100.0));
-        }
-    });
+    orb_q
+        .par_iter_mut()
+        .for_each(|(mut tr, mut mat, time, kind)| {
+            let dt = ct - time.0;
+            let burn_time = kind.copied().unwrap_or_default().params().burn_time;
+
+            /*         if dt > 2 * burn_time {
+                mat.0 = orb_assets.orb_materials[3].clone();
+            } else  */
+            if dt > burn_time {
+                mat.0 = orb_assets.orb_materials[2].clone();
+                tr.scale = tr.scale.lerp(tr.scale * 0.996, 60.0 * delta);
+            } else {
+                mat.0 = orb_assets.orb_materials[1].clone();
+                tr.scale = tr
+                    .scale
+                    .lerp(tr.scale * 1.013, 60.0 * delta)
+                    .min(Vec3::splat(100.0));
+            }
+        });
+}
+
+/// Heats the ship up while it flies into burning orbs in their hot phase, and cools it down otherwise.
+/// Only the flames in front of the ship count, the ship isn't hurt by the gas it ignites behind itself.
+/// The heat itself is reset with the rest of the damage when a run starts.
+fn update_heat_damage(
+    player: Single<&Transform, With<Player>>,
+    flame_tree: Res<KDTree2<BurningGasOrb>>,
+    orb_q: Query<(&Transform, &BurningGasOrb, Option<&GasKind>), Without<Player>>,
+    mut heat: ResMut<HeatDamage>,
+    time: Res<Time<Physics>>,
+) {
+    let ct = time.elapsed().as_millis() as u32;
+    let delta = time.delta_secs();
+
+    let ship_pos = player.translation.truncate();
+    let forward = player.up().truncate();
+
+    let touching_flames = flame_tree
+        .within_distance(ship_pos, FLAME_SEARCH_RADIUS)
+        .into_iter()
+        .filter_map(|(_, entity)| orb_q.get(entity?).ok())
+        .any(|(tr, burning, kind)| {
+            let relative = tr.translation.truncate() - ship_pos;
+            let reach = tr.scale.x + SHIP_HEAT_RADIUS;
+            let burn_time = kind.copied().unwrap_or_default().params().burn_time;
+
+            ct.saturating_sub(burning.0) <= burn_time
+                && relative.length_squared() < reach * reach
+                && relative.dot(forward) > 0.0
+        });
+
+    heat.0 = if touching_flames {
+        (heat.0 + HEAT_RATE * delta).min(1.0)
+    } else {
+        (heat.0 - COOLING_RATE * delta).max(0.0)
+    };
 }
@@ -0,0 +1,354 @@
+//! Simulation of the fire spreading through the gas clouds.
+//!
+//! The fire is a set of fronts. Once its delay has passed, a front ignites the gas orbs
+//! under it and spawns weaker fronts next to it, unless there was nothing to burn.
+//! This module is plain data and doesn't depend on the renderer, the fuel is abstracted by [`Fuel`].
+
+use std::collections::VecDeque;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+/// Base size of a front.
+pub const CELL_SIZE: f32 = 16.;
+/// Maximum number of active fronts, new fronts are dropped above it.
+pub const MAX_FRONTS: usize = 1000;
+/// Number of times the fire can spread from the ignition point.
+pub const LIFETIME: u32 = 30;
+/// Additional delay (in ms) before spreading, per generation. The fire slows down as it spreads.
+pub const SLOWDOWN: u32 = 10;
+/// Delay (in ms) before a fresh front spreads.
+pub const BASE_DELAY: u32 = 15;
+
+/// Relative sizes of the fronts, averaging 0.56, so the fire doesn't look grid-like.
+const SIZE_VARIATION: [f32; 5] = [0.3, 0.7, 0.4, 0.6, 0.8];
+
+/// Anything the fire can spread through, usually a KD-tree of the orbs.
+pub trait Fuel {
+    /// Calls `f` with every entity within `radius` of `pos` and its flammability.
+    /// The flammability is how fast the fire spreads through it, 1.0 for standard gas
+    /// and 0.0 for things that don't burn.
+    fn for_each_within(&self, pos: Vec2, radius: f32, f: impl FnMut(Entity, f32));
+}
+
+/// A list of positioned entities works as fuel, it's slow but handy for tests and tools.
+impl Fuel for [(Vec2, Entity)] {
+    fn for_each_within(&self, pos: Vec2, radius: f32, mut f: impl FnMut(Entity, f32)) {
+        for (p, entity) in self {
+            if p.distance_squared(pos) <= radius * radius {
+                f(*entity, 1.0);
+            }
+        }
+    }
+}
+
+/// Same as above, with the flammability of every entity.
+impl Fuel for [(Vec2, Entity, f32)] {
+    fn for_each_within(&self, pos: Vec2, radius: f32, mut f: impl FnMut(Entity, f32)) {
+        for (p, entity, flammability) in self {
+            if p.distance_squared(pos) <= radius * radius {
+                f(*entity, *flammability);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FireFront {
+    pub pos: Vec2,
+    /// Time (in ms of physics time) the front appeared.
+    pub ignited_at: u32,
+    /// Number of times it can spread further, [`LIFETIME`] at the ignition point.
+    pub life: u32,
+    /// Flammability of the gas that fed the front, it spreads faster through volatile gas.
+    pub speed: f32,
+}
+
+impl FireFront {
+    pub fn new(pos: Vec2, now: u32, life: u32, speed: f32) -> Self {
+        Self {
+            pos,
+            ignited_at: now,
+            life,
+            speed,
+        }
+    }
+
+    /// 1.0 at the ignition point, falls to 0.0 as the fire spreads.
+    pub fn intensity(&self) -> f32 {
+        self.life as f32 / LIFETIME as f32
+    }
+
+    pub fn age(&self, now: u32) -> u32 {
+        now.saturating_sub(self.ignited_at)
+    }
+
+    /// Diameter of the front. It only depends on the position so the simulation stays deterministic.
+    pub fn size(&self) -> f32 {
+        let hash = self.pos.x.to_bits().wrapping_mul(0x9e37_79b9)
+            ^ self.pos.y.to_bits().wrapping_mul(0x85eb_ca6b);
+        CELL_SIZE * SIZE_VARIATION[(hash >> 16) as usize % SIZE_VARIATION.len()]
+    }
+
+    /// Time when the front spreads, the older the fire is the longer it takes.
+    pub fn spreads_at(&self) -> u32 {
+        let delay = BASE_DELAY + SLOWDOWN * LIFETIME.saturating_sub(self.life);
+        self.ignited_at + (delay as f32 / self.speed.max(0.1)) as u32
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        let r = self.size() / 2.0;
+        self.pos.distance_squared(p) <= r * r
+    }
+}
+
+/// What happened during a [`FireField::step`].
+#[derive(Default, Debug)]
+pub struct FireStep {
+    /// Gas orbs that caught fire.
+    pub ignited: Vec<Entity>,
+    /// Red orbs reached by the fire.
+    pub red_orbs: Vec<Entity>,
+}
+
+/// State of the fire in the world.
+#[derive(Resource, Default, Debug)]
+pub struct FireField {
+    fronts: VecDeque<FireFront>,
+    /// Indices of the fronts by the [`CELL_SIZE`] cell their center is in.
+    /// Fronts are smaller than a cell, so a point can only be covered by the fronts of its neighbourhood.
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+fn cell_of(p: Vec2) -> IVec2 {
+    (p / CELL_SIZE).floor().as_ivec2()
+}
+
+impl FireField {
+    /// Starts a fire at the given point.
+    pub fn ignite(&mut self, pos: Vec2, now: u32) {
+        if self.fronts.len() < MAX_FRONTS {
+            self.cells
+                .entry(cell_of(pos))
+                .or_default()
+                .push(self.fronts.len());
+            self.fronts
+                .push_back(FireFront::new(pos, now, LIFETIME, 1.0));
+        }
+    }
+
+    /// Advances the fire to `now`. Given the same fuel and times, the result is always the same.
+    pub fn step(
+        &mut self,
+        now: u32,
+        gas: &(impl Fuel + ?Sized),
+        red_orbs: &(impl Fuel + ?Sized),
+    ) -> FireStep {
+        let mut result = FireStep::default();
+        let mut new_fronts = vec![];
+
+        self.fronts.retain(|front| {
+            if front.life == 0 {
+                return false;
+            }
+            if now <= front.spreads_at() {
+                return true;
+            }
+
+            let radius = front.size() / 2.0;
+
+            // the fastest burning gas under the front, 0.0 if nothing burnt
+            let mut speed = 0.0f32;
+            gas.for_each_within(front.pos, radius, |entity, flammability| {
+                if flammability > 0.0 {
+                    result.ignited.push(entity);
+                    speed = speed.max(flammability);
+                }
+            });
+            red_orbs.for_each_within(front.pos, radius, |entity, _| result.red_orbs.push(entity));
+
+            // the fire dies out without fuel
+            if speed > 0.0 && front.life > 1 {
+                let size = front.size();
+                for dir in [Vec2::Y, Vec2::NEG_Y, Vec2::X, Vec2::NEG_X] {
+                    new_fronts.push(FireFront::new(
+                        front.pos + dir * size,
+                        now,
+                        front.life - 1,
+                        speed,
+                    ));
+                }
+            }
+
+            false
+        });
+
+        let free_space = MAX_FRONTS.saturating_sub(self.fronts.len());
+        self.fronts.extend(new_fronts.into_iter().take(free_space));
+
+        self.cells.clear();
+        for (i, front) in self.fronts.iter().enumerate() {
+            self.cells.entry(cell_of(front.pos)).or_default().push(i);
+        }
+
+        result
+    }
+
+    /// The fronts that may cover the point.
+    fn fronts_near(&self, p: Vec2) -> impl Iterator<Item = &FireFront> {
+        let cell = cell_of(p);
+        (-1..=1)
+            .flat_map(move |y| (-1..=1).map(move |x| cell + IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&i| &self.fronts[i])
+    }
+
+    /// Whether the point is inside an active front.
+    pub fn is_burning(&self, p: Vec2) -> bool {
+        self.fronts_near(p).any(|front| front.contains(p))
+    }
+
+    /// The strongest front covering the point, 0.0 if the point isn't burning.
+    pub fn intensity_at(&self, p: Vec2) -> f32 {
+        self.fronts_near(p)
+            .filter(|front| front.contains(p))
+            .map(FireFront::intensity)
+            .fold(0.0, f32::max)
+    }
+
+    pub fn fronts(&self) -> impl Iterator<Item = &FireFront> {
+        self.fronts.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fronts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fronts.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.fronts.clear();
+        self.cells.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_RED_ORBS: &[(Vec2, Entity)] = &[];
+
+    /// Orbs every 2 units along the x axis, closer than the smallest front.
+    fn orb_line(world: &mut World, from: f32, to: f32) -> Vec<(Vec2, Entity)> {
+        let mut orbs = vec![];
+        let mut x = from;
+        while x <= to {
+            orbs.push((Vec2::new(x, 0.0), world.spawn_empty().id()));
+            x += 2.0;
+        }
+        orbs
+    }
+
+    /// Steps the fire every 5 ms from `from` to `until`, returns every orb that caught fire.
+    /// Like in the game, burning orbs aren't fuel anymore.
+    fn burn(
+        fire: &mut FireField,
+        gas: &mut Vec<(Vec2, Entity)>,
+        red_orbs: &[(Vec2, Entity)],
+        from: u32,
+        until: u32,
+    ) -> Vec<(Vec2, Entity)> {
+        let mut ignited = vec![];
+        for now in (from..=until).step_by(5) {
+            let step = fire.step(now, &gas[..], red_orbs);
+            gas.retain(|orb| {
+                let burning = step.ignited.contains(&orb.1);
+                if burning {
+                    ignited.push(*orb);
+                }
+                !burning
+            });
+        }
+        ignited
+    }
+
+    #[test]
+    fn ignites_the_gas_under_the_ignition() {
+        let mut world = World::new();
+        let orb = world.spawn_empty().id();
+        let far_orb = world.spawn_empty().id();
+        let red_orb = world.spawn_empty().id();
+        let gas = [(Vec2::ZERO, orb), (Vec2::new(0.0, 500.0), far_orb)];
+        let red_orbs = [(Vec2::new(1.0, 0.0), red_orb)];
+
+        let mut fire = FireField::default();
+        fire.ignite(Vec2::ZERO, 0);
+        assert!(fire.is_burning(Vec2::ZERO));
+        assert_eq!(fire.intensity_at(Vec2::ZERO), 1.0);
+        assert!(!fire.is_burning(Vec2::new(0.0, 500.0)));
+
+        // nothing happens before the front spreads
+        let early = fire.step(BASE_DELAY, &gas[..], &red_orbs[..]);
+        assert!(early.ignited.is_empty());
+        assert_eq!(fire.len(), 1);
+
+        let step = fire.step(BASE_DELAY + 1, &gas[..], &red_orbs[..]);
+        assert_eq!(step.ignited, vec![orb]);
+        assert_eq!(step.red_orbs, vec![red_orb]);
+        // the front is replaced by weaker ones around it
+        assert_eq!(fire.len(), 4);
+        assert!(fire.intensity_at(Vec2::ZERO) < 1.0);
+    }
+
+    #[test]
+    fn spreads_to_the_neighbours() {
+        let mut world = World::new();
+        let mut gas = orb_line(&mut world, -60.0, 60.0);
+        let lonely_orb = world.spawn_empty().id();
+        gas.push((Vec2::new(0.0, 300.0), lonely_orb));
+
+        let mut fire = FireField::default();
+        fire.ignite(Vec2::ZERO, 0);
+        let ignited = burn(&mut fire, &mut gas, NO_RED_ORBS, 5, 2000);
+
+        assert!(ignited.iter().any(|(pos, _)| pos.x >= 50.0));
+        assert!(ignited.iter().any(|(pos, _)| pos.x <= -50.0));
+        assert!(!ignited.iter().any(|(_, orb)| *orb == lonely_orb));
+    }
+
+    #[test]
+    fn non_flammable_gas_stops_the_fire() {
+        let mut world = World::new();
+        let coolant: Vec<_> = orb_line(&mut world, -60.0, 60.0)
+            .into_iter()
+            .map(|(pos, entity)| (pos, entity, 0.0))
+            .collect();
+
+        let mut fire = FireField::default();
+        fire.ignite(Vec2::ZERO, 0);
+        for now in (5..=100).step_by(5) {
+            let step = fire.step(now, &coolant[..], NO_RED_ORBS);
+            assert!(step.ignited.is_empty());
+        }
+        assert!(fire.is_empty());
+        assert!(!fire.is_burning(Vec2::ZERO));
+    }
+
+    #[test]
+    fn burns_out() {
+        let mut world = World::new();
+        let mut gas = orb_line(&mut world, -1000.0, 1000.0);
+
+        let mut fire = FireField::default();
+        fire.ignite(Vec2::ZERO, 0);
+        burn(&mut fire, &mut gas, NO_RED_ORBS, 5, 500);
+        assert!(!fire.is_empty());
+
+        // the fire spreads at most LIFETIME times, slower and slower, long before it runs out of gas
+        burn(&mut fire, &mut gas, NO_RED_ORBS, 505, 10_000);
+        assert!(fire.is_empty());
+        assert!(!gas.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Gas comes in several kinds, each burning differently and having its own effect on the ship.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GasKind>().register_type::<FuelMix>();
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum GasKind {
+    #[default]
+    Standard,
+    /// Burns fast, gives a strong but short boost and spreads the fire quickly.
+    Volatile,
+    /// Doesn't burn, it slows the ship down.
+    Coolant,
+    /// Makes the ship turn much faster.
+    Ionized,
+}
+
+impl GasKind {
+    pub const COUNT: usize = 4;
+    pub const ALL: [GasKind; Self::COUNT] = [
+        GasKind::Standard,
+        GasKind::Volatile,
+        GasKind::Coolant,
+        GasKind::Ionized,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GasKind::Standard => "Standard",
+            GasKind::Volatile => "Volatile",
+            GasKind::Coolant => "Coolant",
+            GasKind::Ionized => "Ionized",
+        }
+    }
+
+    pub fn params(self) -> GasParams {
+        match self {
+            GasKind::Standard => GasParams {
+                boost: 1.0,
+                burn_rate: 0.01,
+                turning: 1.0,
+                drag: 0.0,
+                flammability: 1.0,
+                burn_time: 300,
+            },
+            GasKind::Volatile => GasParams {
+                boost: 1.7,
+                burn_rate: 0.0005,
+                turning: 1.0,
+                drag: 0.0,
+                flammability: 2.0,
+                burn_time: 150,
+            },
+            GasKind::Coolant => GasParams {
+                boost: 0.2,
+                burn_rate: 0.05,
+                turning: 1.0,
+                drag: 0.4,
+                flammability: 0.0,
+                burn_time: 300,
+            },
+            GasKind::Ionized => GasParams {
+                boost: 1.0,
+                burn_rate: 0.01,
+                turning: 2.0,
+                drag: 0.0,
+                flammability: 1.0,
+                burn_time: 300,
+            },
+        }
+    }
+}
+
+/// How a kind of gas behaves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GasParams {
+    /// Multiplier of the gas boost.
+    pub boost: f32,
+    /// Fraction of the gas left in the engine after a second.
+    pub burn_rate: f32,
+    /// Multiplier of the ship rotation speed at full tank.
+    pub turning: f32,
+    /// Braking force per unit of velocity at full tank.
+    pub drag: f32,
+    /// How fast the fire spreads through the gas, 0 means it doesn't burn at all.
+    pub flammability: f32,
+    /// Duration of the hot phase of a burning orb, in ms.
+    pub burn_time: u32,
+}
+
+/// Composition of the gas currently in the engine, the amounts of each [`GasKind`] taken in recently.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+pub struct FuelMix(pub [f32; GasKind::COUNT]);
+
+impl FuelMix {
+    pub fn add(&mut self, kind: GasKind, amount: f32) {
+        self.0[kind as usize] += amount;
+    }
+
+    pub fn total(&self) -> f32 {
+        self.0.iter().sum()
+    }
+
+    /// The kind of gas making up most of the mix, `None` when the mix is empty.
+    pub fn dominant(&self) -> Option<GasKind> {
+        GasKind::ALL
+            .into_iter()
+            .filter(|kind| self.0[*kind as usize] > 0.0)
+            .max_by(|a, b| self.0[*a as usize].total_cmp(&self.0[*b as usize]))
+    }
+
+    /// Parameters of the kinds blended by their share of the mix, standard gas when the mix is empty.
+    pub fn params(&self) -> GasParams {
+        let total = self.total();
+        if total <= 0.0 {
+            return GasKind::Standard.params();
+        }
+
+        let mut params = GasParams {
+            boost: 0.0,
+            burn_rate: 0.0,
+            turning: 0.0,
+            drag: 0.0,
+            flammability: 0.0,
+            burn_time: 0,
+        };
+        let mut burn_time = 0.0;
+
+        for kind in GasKind::ALL {
+            let share = self.0[kind as usize] / total;
+            let p = kind.params();
+            params.boost += p.boost * share;
+            params.burn_rate += p.burn_rate * share;
+            params.turning += p.turning * share;
+            params.drag += p.drag * share;
+            params.flammability += p.flammability * share;
+            burn_time += p.burn_time as f32 * share;
+        }
+        params.burn_time = burn_time as u32;
+
+        params
+    }
+
+    /// Scales every amount, keeping the composition.
+    pub fn scale(&mut self, factor: f32) {
+        for amount in &mut self.0 {
+            *amount *= factor;
+        }
+    }
+}
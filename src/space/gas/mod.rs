@@ -9,31 +9,54 @@ use crate::{
     PausableSystems,
     player::movement::CurrentGas,
     screens::Screen,
-    space::gas::{assets::OrbAssets, burn::BurnEvent},
+    simulation::is_headless,
+    space::{
+        biome::OrbBiome,
+        gas::{
+            assets::OrbAssets,
+            burn::BurnEvent,
+            kind::{FuelMix, GasKind},
+        },
+    },
 };
 
 pub mod assets;
 pub mod burn;
+pub mod fire;
+pub mod kind;
+mod sound;
 
 use burn::propagate_flames;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         AutomaticUpdate::<GasOrb>::new()
+            .with_schedule(FixedUpdate)
             .with_spatial_ds(SpatialStructure::KDTree2)
             .with_frequency(Duration::from_secs_f32(0.3))
             .with_transform(TransformMode::GlobalTransform),
+        // the flames hurting the ship, updated often as the fire spreads fast
+        AutomaticUpdate::<BurningGasOrb>::new()
+            .with_schedule(FixedUpdate)
+            .with_spatial_ds(SpatialStructure::KDTree2)
+            .with_frequency(Duration::from_secs_f32(0.05))
+            .with_transform(TransformMode::GlobalTransform),
         assets::plugin,
         burn::plugin,
+        kind::plugin,
     ))
     .add_observer(orb_setup)
     .add_systems(
-        Update,
+        FixedUpdate,
         ignite_gas
             .before(propagate_flames)
             .run_if(in_state(Screen::Gameplay))
             .in_set(PausableSystems),
     );
+
+    if !is_headless(app) {
+        app.add_plugins(sound::plugin);
+    }
 }
 
 #[derive(Component)]
@@ -42,25 +65,44 @@ pub struct GasOrb(pub f32); // contains it's mass
 #[derive(Component)]
 pub struct BurningGasOrb(pub u32); // time when it started burning in ms
 
+/// A gas orb the ship already took in. It can still burn, but it gives no more gas.
+#[derive(Component)]
+pub struct Spent;
+
+/// Heat the player soaked up by flying into burning gas. When it reaches 1.0, the player must die.
+#[derive(Resource, Default)]
+pub struct HeatDamage(pub f32);
+
 // both should not be high
 pub const IGNITION_OFFSET: f32 = 10.0;
 pub const IGNITION_RADIUS: f32 = 13.0;
 
-fn orb_setup(trigger: On<Add, GasOrb>, mut cmds: Commands, gas_assets: Res<OrbAssets>) {
-    cmds.entity(trigger.event().event_target()).insert((
+fn orb_setup(
+    trigger: On<Add, GasOrb>,
+    mut cmds: Commands,
+    gas_assets: Res<OrbAssets>,
+    q_orbs: Query<(Option<&OrbBiome>, Option<&GasKind>)>,
+) {
+    let entity = trigger.event().event_target();
+    let (biome, kind) = q_orbs.get(entity).unwrap_or_default();
+    let biome = biome.map(|b| b.0).unwrap_or_default();
+    let kind = kind.copied().unwrap_or_default();
+
+    cmds.entity(entity).insert((
         Mesh3d(gas_assets.orb_mesh.clone()),
-        MeshMaterial3d(gas_assets.orb_materials[0].clone()),
+        MeshMaterial3d(gas_assets.orb_material(biome, kind)),
     ));
 }
 
 // we can accelerate right here I guess... no need we already run before thrust. maybe pipe them then?
 pub fn ignite_gas(
-    q_orbs: Query<&GasOrb>,
-    q_ship: Single<(&Transform, &mut CurrentGas)>,
+    mut commands: Commands,
+    q_orbs: Query<(&GasOrb, Option<&GasKind>), Without<Spent>>,
+    q_ship: Single<(&Transform, &mut CurrentGas, &mut FuelMix)>,
     tree: Res<KDTree2<GasOrb>>,
     mut ignite_gas_tx: MessageWriter<BurnEvent>,
 ) {
-    let (ship_tr, mut gas) = q_ship.into_inner();
+    let (ship_tr, mut gas, mut mix) = q_ship.into_inner();
 
     let backward = ship_tr.down().truncate();
     let ship_tr_2d = ship_tr.translation.truncate();
@@ -78,7 +120,14 @@ pub fn ignite_gas(
             .clamp(0.0, 1.0);
         if let Some(e) = e {
             count += 1;
-            q_orbs.get(e).map(|g| total_gas += k * g.0).ok();
+            if let Ok((orb, kind)) = q_orbs.get(e)
+                && k * orb.0 > 0.0
+            {
+                // each orb is taken in once, coolant never burns away and would refuel forever
+                total_gas += k * orb.0;
+                mix.add(kind.copied().unwrap_or_default(), k * orb.0);
+                commands.entity(e).try_insert(Spent);
+            }
         }
     }
 
@@ -93,6 +142,12 @@ pub fn ignite_gas(
     }
 
     gas.0 = (gas.0 + total_gas).min(1.0);
+
+    // only the composition of the mix matters, keep it from growing forever
+    let mix_total = mix.total();
+    if mix_total > 1.0 {
+        mix.scale(1.0 / mix_total);
+    }
 }
 
 // fn attract_gas(
@@ -0,0 +1,66 @@
+//! Crackle of the burning gas, heard from where the flames catch.
+
+use bevy::prelude::*;
+use bevy_kira_audio::AudioChannel;
+
+use crate::{
+    PausableSystems,
+    audio::{
+        AudioAssets, SfxChannel,
+        spatial::{AudioListener, SpatialAudio},
+    },
+    screens::Screen,
+};
+
+use super::BurningGasOrb;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        crackle
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<AudioAssets>))
+            .in_set(PausableSystems),
+    );
+}
+
+/// Minimum time between two crackles, whole clouds catch fire at once.
+const CRACKLE_INTERVAL_SECS: f32 = 0.08;
+const CRACKLE_VOLUME: f32 = 0.4;
+
+/// Plays a crackle at the newly ignited orb closest to the listener.
+fn crackle(
+    mut commands: Commands,
+    ignited: Query<&GlobalTransform, Added<BurningGasOrb>>,
+    listener: Single<&GlobalTransform, With<AudioListener>>,
+    audio: Res<AudioChannel<SfxChannel>>,
+    spatial_audio: SpatialAudio,
+    audio_assets: Res<AudioAssets>,
+    time: Res<Time>,
+    mut last_crackle: Local<f32>,
+) {
+    let now = time.elapsed_secs();
+    if now - *last_crackle < CRACKLE_INTERVAL_SECS {
+        return;
+    }
+
+    let listener_pos = listener.translation();
+    let Some(closest) = ignited
+        .iter()
+        .map(GlobalTransform::translation)
+        .min_by(|a, b| {
+            a.distance_squared(listener_pos)
+                .total_cmp(&b.distance_squared(listener_pos))
+        })
+    else {
+        return;
+    };
+
+    *last_crackle = now;
+    // one of two ticks at random, so it sounds less mechanical
+    let source = if rand::random::<bool>() {
+        audio_assets.tick_short.clone()
+    } else {
+        audio_assets.tick_norm.clone()
+    };
+    spatial_audio.play_at(&mut commands, &audio, source, closest, CRACKLE_VOLUME);
+}
@@ -0,0 +1,228 @@
+//! Generation of the chunk content as plain data.
+//!
+//! Nothing here touches the ECS, so chunks can be generated on a background thread
+//! and only committed to the world once they are ready.
+
+use bevy::prelude::*;
+use rand::RngExt as _;
+
+use crate::{
+    asteroids::{Asteroid, MAX_DRIFT_SPEED, MAX_SPIN},
+    red_gas::RedGasOrb,
+};
+
+use super::{
+    GasGenerator, INTRO_SCENE_RADIUS_SQ, WorldSeed,
+    biome::Biome,
+    config::WorldGenConfig,
+    gas::kind::GasKind,
+    gravity::{GravityBody, GravityKind},
+    smoothstep,
+};
+
+/// A gas orb that is about to be spawned.
+#[derive(Clone, Debug)]
+pub struct OrbSpawn {
+    pub pos: Vec3,
+    pub mass: f32,
+    pub biome: Biome,
+    pub kind: GasKind,
+}
+
+/// Everything a chunk contains right after generation.
+#[derive(Default)]
+pub struct ChunkContent {
+    pub orbs: Vec<OrbSpawn>,
+    pub red_orbs: Vec<RedGasOrb>,
+    pub asteroids: Vec<Asteroid>,
+    pub gravity_bodies: Vec<GravityBody>,
+}
+
+fn asteroid_distribution(r: f32) -> f32 {
+    let a = smoothstep(-0.5, -0.3, r);
+    let b = smoothstep(-0.1, -0.3, r);
+
+    a.min(b)
+}
+
+fn explosive_orb_distribution(r: f32) -> f32 {
+    let a = smoothstep(0.3, 0.35, r);
+    let b = smoothstep(0.5, 0.35, r);
+
+    a.min(b)
+}
+
+/// Drift velocity and spin of an asteroid. They are hashed from its position rather than drawn
+/// from the chunk RNG, so the rest of the chunk is laid out the same as before asteroids moved.
+fn asteroid_motion(seed: WorldSeed, pos: Vec2) -> (Vec2, f32) {
+    let hash = super::splitmix64(
+        seed.0 as u64 ^ (((pos.x.to_bits() as u64) << 32) | pos.y.to_bits() as u64),
+    );
+    let unit = |bits: u64| (bits & 0xffff) as f32 / 0xffff as f32;
+
+    let direction = Vec2::from_angle(unit(hash) * std::f32::consts::TAU);
+    let speed = unit(hash >> 16) * MAX_DRIFT_SPEED;
+    let spin = (unit(hash >> 32) * 2.0 - 1.0) * MAX_SPIN;
+
+    (direction * speed, spin)
+}
+
+/// Rolls the rare gravity body of a chunk. It sits near the middle of the chunk,
+/// so the gas it distorts never crosses into the neighbouring chunks.
+fn generate_gravity_body(
+    config: &WorldGenConfig,
+    seed: WorldSeed,
+    chunk_coords: IVec2,
+) -> Option<GravityBody> {
+    let mut rng = seed.feature_rng(chunk_coords);
+    let gravity = &config.gravity;
+
+    let chunk_pos = chunk_coords.as_vec2() * config.chunk_size;
+    let pos = chunk_pos
+        + (Vec2::splat(0.375) + Vec2::new(rng.random::<f32>(), rng.random::<f32>()) * 0.25)
+            * config.chunk_size;
+
+    // far enough from the start that the intro stays the same
+    let is_intro_region = pos.length_squared() < 4.0 * INTRO_SCENE_RADIUS_SQ;
+    if is_intro_region || rng.random::<f32>() >= gravity.chance {
+        return None;
+    }
+
+    let kind = if rng.random::<f32>() < gravity.black_hole_chance {
+        GravityKind::BlackHole
+    } else {
+        GravityKind::Well
+    };
+
+    Some(GravityBody {
+        pos: pos.extend(0.0),
+        kind,
+        strength: gravity.min_strength + gravity.strength_variation * rng.random::<f32>(),
+        influence_radius: gravity.influence_radius,
+        horizon_radius: gravity.horizon_radius,
+    })
+}
+
+/// Generates the content of a chunk.
+/// The chunk is first subdivided into [`WorldGenConfig::chunk_subdiv`] parts along each axis,
+/// then each cell may spawn an orb depending on randomness and underlying space parameters.
+pub fn generate_chunk(
+    gas: &GasGenerator,
+    config: &WorldGenConfig,
+    seed: WorldSeed,
+    chunk_coords: IVec2,
+) -> ChunkContent {
+    let mut rng = seed.chunk_rng(chunk_coords);
+    let mut content = ChunkContent::default();
+
+    let gravity_body = generate_gravity_body(config, seed, chunk_coords);
+    // nothing solid spawns in the swirl of a black hole
+    let is_swirled = |pos: Vec2| {
+        gravity_body.as_ref().is_some_and(|body| {
+            body.kind == GravityKind::BlackHole
+                && body.pos_2d().distance_squared(pos)
+                    < config.gravity.lens_radius * config.gravity.lens_radius
+        })
+    };
+
+    let chunk_size = config.chunk_size;
+    let chunk_subdiv = config.chunk_subdiv();
+
+    for y in 0..chunk_subdiv {
+        for x in 0..chunk_subdiv {
+            let cell_pos = chunk_coords.as_vec2() * chunk_size
+                + (Vec2::new(x as f32, y as f32) / (chunk_subdiv as f32)) * chunk_size;
+            let r = gas.sample(cell_pos);
+            let biome_weights = gas.biome_weights(cell_pos);
+            let biome = biome_weights.params(&config.biomes);
+
+            if r > biome.orb_threshold {
+                // The actual orb position is slightly offset to avoid a grid-like look
+                let pos = cell_pos
+                    + Vec2::new(rng.random::<f32>(), rng.random::<f32>()) * chunk_size
+                        / chunk_subdiv as f32;
+
+                // todo: we can vary that 0.5 with another noise for more depth effect
+                let z = (rng.random::<f32>() - 0.5) * config.orbs.cloud_z_scale * r;
+                let orb_biome = biome_weights.pick(rng.random::<f32>());
+
+                // black holes bend the cloud around them, or swallow the orb
+                let lensed = match &gravity_body {
+                    Some(body) => body.lens(&config.gravity, pos),
+                    None => Some(pos),
+                };
+
+                if let Some(pos) = lensed {
+                    content.orbs.push(OrbSpawn {
+                        pos: pos.extend(z),
+                        mass: r,
+                        biome: orb_biome,
+                        kind: gas.gas_kind(pos),
+                    });
+                }
+            }
+
+            let explosive_orb_r = explosive_orb_distribution(r);
+
+            if explosive_orb_r > 0.70 {
+                if rng.random::<f32>() >= biome.red_orb_chance {
+                    continue;
+                }
+
+                let pos = cell_pos;
+
+                let r = rng.random::<f32>();
+                let orb_size = config.red_orbs.min_size + config.red_orbs.size_variation * r;
+                let z = (rng.random::<f32>() - 0.5) * config.red_orbs.cloud_z_scale;
+                if !is_swirled(pos) {
+                    content.red_orbs.push(RedGasOrb {
+                        pos: pos.extend(z),
+                        radius: orb_size,
+                    });
+                }
+            }
+        }
+
+        const ASTEROID_SPAWN_SCALE: f32 = 0.2;
+
+        let asteroid_chunk_subdiv = ((chunk_subdiv as f32) * ASTEROID_SPAWN_SCALE) as u32;
+        for y in 0..asteroid_chunk_subdiv {
+            for x in 0..asteroid_chunk_subdiv {
+                let cell_pos = chunk_coords.as_vec2() * chunk_size
+                    + (Vec2::new(x as f32, y as f32) / (asteroid_chunk_subdiv as f32)) * chunk_size;
+
+                let r = gas.sample(cell_pos);
+                let biome = gas.biome_weights(cell_pos).params(&config.biomes);
+
+                let is_intro_region = cell_pos.length_squared() < INTRO_SCENE_RADIUS_SQ;
+                let meteorite_r = asteroid_distribution(r);
+                if meteorite_r > 0.60 && !is_intro_region {
+                    if rng.random::<f32>() >= biome.asteroid_chance {
+                        continue;
+                    }
+                    let pos = cell_pos
+                        + Vec2::new(rng.random::<f32>(), rng.random::<f32>()) * chunk_size
+                            / chunk_subdiv as f32;
+
+                    let r = rng.random::<f32>();
+                    let asteroid_size =
+                        config.asteroids.min_size + config.asteroids.size_variation * r;
+                    let z = (rng.random::<f32>() - 0.5) * config.asteroids.cloud_z_scale;
+                    if !is_swirled(pos) {
+                        let (velocity, spin) = asteroid_motion(seed, pos);
+                        content.asteroids.push(Asteroid {
+                            pos: pos.extend(z),
+                            radius: asteroid_size,
+                            velocity,
+                            spin,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    content.gravity_bodies.extend(gravity_body);
+
+    content
+}
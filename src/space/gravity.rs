@@ -0,0 +1,345 @@
+//! Gravity wells and black holes.
+//!
+//! They are rare bodies scattered by the world generation. Their pull bends the trajectory of the ship,
+//! drags the nearby gas orbs and red orb explosions, and a tight enough slingshot around one earns aura.
+//! Black holes also swallow whatever crosses their horizon, the ship included.
+
+use avian2d::prelude::*;
+use bevy::{
+    color::palettes::css::{BLACK, MEDIUM_PURPLE, ORANGE},
+    prelude::*,
+};
+use bevy_spatial::{SpatialAccess, kdtree::KDTree2};
+
+use crate::{
+    PausableSystems,
+    asset_tracking::LoadResource,
+    player::{Player, movement::AuraEarned},
+    red_gas::RedOrbExplosion,
+    screens::Screen,
+};
+
+use super::{
+    config::GravityConfig,
+    delta::{ChunkDeltas, ChunkItem},
+    gas::GasOrb,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GravityAssets>()
+        .load_resource::<GravityAssets>()
+        .init_resource::<BlackHoleDamage>()
+        .init_resource::<Slingshot>()
+        .add_observer(on_add_gravity_body)
+        .add_systems(OnEnter(Screen::Gameplay), reset_gravity)
+        .add_systems(
+            FixedUpdate,
+            (pull_ship, track_slingshot, pull_gas_orbs, pull_explosions)
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        );
+}
+
+/// Orbs and explosions aren't rigid bodies, they drift at this fraction of the pull instead of accelerating.
+const DRIFT_FACTOR: f32 = 0.05;
+/// Aura earned per unit of speed gained during a slingshot.
+const SLINGSHOT_AURA: f32 = 0.5;
+/// A slingshot only counts if the ship got closer than this fraction of the influence radius.
+const SLINGSHOT_DEPTH: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GravityKind {
+    /// Only pulls.
+    Well,
+    /// Pulls, and swallows everything crossing its horizon.
+    BlackHole,
+}
+
+impl GravityKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            GravityKind::Well => "gravity well",
+            GravityKind::BlackHole => "black hole",
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct GravityBody {
+    pub pos: Vec3,
+    pub kind: GravityKind,
+    /// Acceleration at the center, it fades out towards the edge of the influence.
+    pub strength: f32,
+    /// Distance the pull reaches.
+    pub influence_radius: f32,
+    /// Radius of the core. Nothing comes out of the core of a black hole.
+    pub horizon_radius: f32,
+}
+
+impl GravityBody {
+    pub fn pos_2d(&self) -> Vec2 {
+        self.pos.truncate()
+    }
+
+    /// Acceleration caused by the body at the given point.
+    pub fn pull_at(&self, p: Vec2) -> Vec2 {
+        let offset = self.pos_2d() - p;
+        let d = offset.length();
+        if d >= self.influence_radius {
+            return Vec2::ZERO;
+        }
+
+        let falloff = 1.0 - d / self.influence_radius;
+        offset.normalize_or_zero() * self.strength * falloff * falloff
+    }
+
+    pub fn is_inside_horizon(&self, p: Vec2) -> bool {
+        self.pos_2d().distance_squared(p) < self.horizon_radius * self.horizon_radius
+    }
+
+    /// Where a gas orb generated at `p` ends up, so the cloud visibly swirls around black holes.
+    /// Orbs too close to the horizon are swallowed right away and `None` is returned.
+    pub fn lens(&self, config: &GravityConfig, p: Vec2) -> Option<Vec2> {
+        if self.kind != GravityKind::BlackHole {
+            return Some(p);
+        }
+
+        let center = self.pos_2d();
+        let offset = p - center;
+        let d = offset.length();
+        if d >= config.lens_radius {
+            return Some(p);
+        }
+        if d < self.horizon_radius * 1.5 {
+            return None;
+        }
+
+        // the closer the orb, the more it is twisted around the hole and squeezed towards its horizon
+        let t = 1.0 - d / config.lens_radius;
+        let twisted = Vec2::from_angle(config.lens_twist * t * t).rotate(offset);
+        let squeezed = d - (d - self.horizon_radius * 1.5) * 0.5 * t;
+        Some(center + twisted.normalize_or_zero() * squeezed)
+    }
+}
+
+/// Set to 1.0 when the ship crosses the horizon of a black hole, which destroys it.
+#[derive(Resource, Default)]
+pub struct BlackHoleDamage(pub f32);
+
+/// A slingshot maneuver in progress around a gravity body.
+#[derive(Resource, Default, Debug)]
+pub struct Slingshot {
+    body: Option<Entity>,
+    entry_speed: f32,
+    /// Closest distance to the body so far.
+    closest: f32,
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct GravityAssets {
+    #[dependency]
+    pub disc_mesh: Handle<Mesh>,
+    #[dependency]
+    pub ring_mesh: Handle<Mesh>,
+    #[dependency]
+    pub horizon_material: Handle<StandardMaterial>,
+    #[dependency]
+    pub accretion_material: Handle<StandardMaterial>,
+    #[dependency]
+    pub well_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for GravityAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+
+        Self {
+            disc_mesh: assets.add(Circle::new(1.0).mesh().resolution(32).build()),
+            ring_mesh: assets.add(Annulus::new(1.2, 2.2).mesh().resolution(48).build()),
+            horizon_material: assets.add(StandardMaterial {
+                base_color: BLACK.into(),
+                unlit: true,
+                ..Default::default()
+            }),
+            accretion_material: assets.add(StandardMaterial {
+                base_color: ORANGE.with_alpha(0.6).into(),
+                alpha_mode: AlphaMode::Blend,
+                emissive: (ORANGE * 4.0).into(),
+                ..Default::default()
+            }),
+            well_material: assets.add(StandardMaterial {
+                base_color: MEDIUM_PURPLE.with_alpha(0.15).into(),
+                alpha_mode: AlphaMode::Blend,
+                emissive: (MEDIUM_PURPLE * 0.8).into(),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+fn on_add_gravity_body(
+    trigger: On<Add, GravityBody>,
+    mut commands: Commands,
+    bodies: Query<&GravityBody>,
+    assets: Res<GravityAssets>,
+) {
+    let entity = trigger.event().event_target();
+    let Ok(body) = bodies.get(entity) else {
+        return;
+    };
+
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.insert((
+        Name::new(body.kind.name()),
+        Transform::from_translation(body.pos),
+        Visibility::Visible,
+    ));
+
+    match body.kind {
+        GravityKind::BlackHole => {
+            entity_commands.with_children(|builder| {
+                builder.spawn((
+                    Mesh3d(assets.disc_mesh.clone()),
+                    MeshMaterial3d(assets.horizon_material.clone()),
+                    Transform::from_scale(Vec3::splat(body.horizon_radius)),
+                ));
+                builder.spawn((
+                    Mesh3d(assets.ring_mesh.clone()),
+                    MeshMaterial3d(assets.accretion_material.clone()),
+                    Transform::from_scale(Vec3::splat(body.horizon_radius)),
+                ));
+            });
+        }
+        GravityKind::Well => {
+            entity_commands.with_child((
+                Mesh3d(assets.disc_mesh.clone()),
+                MeshMaterial3d(assets.well_material.clone()),
+                Transform::from_scale(Vec3::splat(body.horizon_radius * 2.0)),
+            ));
+        }
+    }
+}
+
+fn reset_gravity(mut damage: ResMut<BlackHoleDamage>, mut slingshot: ResMut<Slingshot>) {
+    damage.0 = 0.0;
+    *slingshot = Slingshot::default();
+}
+
+fn pull_ship(
+    player: Single<(Forces, &Transform), With<Player>>,
+    bodies: Query<&GravityBody>,
+    mut damage: ResMut<BlackHoleDamage>,
+) {
+    let (mut forces, ship_tr) = player.into_inner();
+    let ship_pos = ship_tr.translation.truncate();
+
+    let pull: Vec2 = bodies.iter().map(|body| body.pull_at(ship_pos)).sum();
+    forces.apply_linear_acceleration(pull);
+
+    let swallowed = bodies
+        .iter()
+        .any(|body| body.kind == GravityKind::BlackHole && body.is_inside_horizon(ship_pos));
+    if swallowed {
+        damage.0 = 1.0;
+    }
+}
+
+/// Rewards the ship for gaining speed while swinging around a gravity body.
+fn track_slingshot(
+    player: Single<(&mut Player, &Transform, &LinearVelocity)>,
+    bodies: Query<(Entity, &GravityBody)>,
+    mut slingshot: ResMut<Slingshot>,
+    mut aura_event: MessageWriter<AuraEarned>,
+) {
+    let (mut player, ship_tr, velocity) = player.into_inner();
+    let ship_pos = ship_tr.translation.truncate();
+    let speed = velocity.length();
+
+    let current = slingshot.body.and_then(|entity| bodies.get(entity).ok());
+    match current {
+        Some((_, body)) if body.pull_at(ship_pos) != Vec2::ZERO => {
+            slingshot.closest = slingshot.closest.min(body.pos_2d().distance(ship_pos));
+        }
+        Some((_, body)) => {
+            // the ship left the influence of the body
+            let gain = speed - slingshot.entry_speed;
+            if gain > 0.0 && slingshot.closest < body.influence_radius * SLINGSHOT_DEPTH {
+                let earned = gain * SLINGSHOT_AURA;
+                debug!("slingshot around a {}: +{earned:.0}", body.kind.name());
+                player.aura_points += earned;
+                aura_event.write(AuraEarned(earned));
+            }
+            *slingshot = Slingshot::default();
+        }
+        None => {
+            // the body was unloaded, or there was no slingshot yet
+            *slingshot = Slingshot::default();
+            if let Some((entity, body)) = bodies
+                .iter()
+                .find(|(_, body)| body.pull_at(ship_pos) != Vec2::ZERO)
+            {
+                *slingshot = Slingshot {
+                    body: Some(entity),
+                    entry_speed: speed,
+                    closest: body.pos_2d().distance(ship_pos),
+                };
+            }
+        }
+    }
+}
+
+fn pull_gas_orbs(
+    mut cmds: Commands,
+    bodies: Query<&GravityBody>,
+    mut orbs: Query<(&mut Transform, Option<&ChunkItem>), With<GasOrb>>,
+    tree: Res<KDTree2<GasOrb>>,
+    mut deltas: ResMut<ChunkDeltas>,
+    time: Res<Time<Physics>>,
+) {
+    let delta = time.delta_secs();
+
+    for body in &bodies {
+        for (_, entity) in tree.within_distance(body.pos_2d(), body.influence_radius) {
+            let Some(entity) = entity else {
+                continue;
+            };
+            let Ok((mut tr, item)) = orbs.get_mut(entity) else {
+                continue;
+            };
+
+            let pos = tr.translation.truncate();
+            if body.is_inside_horizon(pos) {
+                if body.kind == GravityKind::BlackHole {
+                    cmds.entity(entity).try_despawn();
+                    if let Some(item) = item {
+                        deltas.record(item);
+                    }
+                }
+                // orbs gather in the core of a gravity well
+                continue;
+            }
+
+            let drift = body.pull_at(pos) * DRIFT_FACTOR * delta;
+            tr.translation += drift.extend(0.0);
+        }
+    }
+}
+
+fn pull_explosions(
+    bodies: Query<&GravityBody>,
+    mut explosions: Query<(&mut RedOrbExplosion, &mut Transform)>,
+    time: Res<Time<Physics>>,
+) {
+    if bodies.is_empty() {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    for (mut explosion, mut tr) in &mut explosions {
+        let pull: Vec2 = bodies.iter().map(|body| body.pull_at(explosion.pos)).sum();
+        let drift = pull * DRIFT_FACTOR * delta;
+        explosion.pos += drift;
+        tr.translation += drift.extend(0.0);
+    }
+}
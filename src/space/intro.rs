@@ -1,21 +1,26 @@
 use avian2d::prelude::LinearVelocity;
 use bevy::{math::VectorSpace, prelude::*};
 
-use crate::{player::Player, screens::Screen};
+use crate::{player::Player, screens::Screen, simulation::is_headless};
 
 pub const INTRO_DURATION_SECS: f32 = 3.0;
 
 pub fn plugin(app: &mut App) {
     app.insert_state(IntroState(false))
         .insert_resource(IntroProgress { t: 0.0 })
-        .add_systems(OnEnter(Screen::Gameplay), setup_intro)
-        .add_systems(OnEnter(IntroState(false)), on_intro_finished)
-        .add_systems(
-            FixedPostUpdate,
-            camera_follow_player
-                .run_if(in_state(Screen::Gameplay))
-                .run_if(in_state(IntroState(true))),
-        );
+        .add_systems(OnEnter(IntroState(false)), on_intro_finished);
+
+    // The intro is a camera flight. Without a camera the run starts right away, so a headless run
+    // plays like the game built with `skip_intro`: the ship keeps its spawn velocity.
+    if !cfg!(feature = "skip_intro") && !is_headless(app) {
+        app.add_systems(OnEnter(Screen::Gameplay), setup_intro)
+            .add_systems(
+                FixedPostUpdate,
+                camera_follow_player
+                    .run_if(in_state(Screen::Gameplay))
+                    .run_if(in_state(IntroState(true))),
+            );
+    }
 }
 
 #[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
@@ -26,8 +31,7 @@ pub struct IntroProgress {
     t: f32,
 }
 
-fn setup_intro(_intro_state: ResMut<NextState<IntroState>>) {
-    #[cfg(not(feature = "skip_intro"))]
+fn setup_intro(mut intro_state: ResMut<NextState<IntroState>>) {
     intro_state.set(IntroState(true));
 }
 
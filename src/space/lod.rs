@@ -0,0 +1,198 @@
+//! Level of detail of the gas orbs.
+//!
+//! Only chunks near the ship need an entity per orb, for ignition and burning.
+//! Further chunks draw all of their orbs as one mesh per biome material, which is much cheaper
+//! to render and doesn't bloat the orb KD-tree.
+
+use avian2d::prelude::Physics;
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{PausableSystems, player::Player, screens::Screen};
+
+use super::{
+    biome::{Biome, OrbBiome},
+    commit_chunks,
+    config::WorldGenConfig,
+    delta::{ChunkDeltas, ChunkItem, ChunkItemKind},
+    gas::{BurningGasOrb, GasOrb, assets::OrbAssets, kind::GasKind},
+    generation::OrbSpawn,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    // on the fixed clock like the chunks, the orbs the ship can ignite must not depend on the frame rate
+    app.add_systems(
+        FixedUpdate,
+        update_chunk_lod
+            .after(commit_chunks)
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(PausableSystems),
+    );
+}
+
+/// Maximum number of chunks switching their level of detail per tick.
+const LOD_SWITCHES_PER_TICK: usize = 3;
+/// A chunk keeps its orb entities for this long (in ms of physics time) after one of them caught fire,
+/// batching it would put the fire out.
+const FIRE_DETAIL_MS: u32 = 5000;
+
+/// Gas orbs generated for a chunk, the index of an orb is its [`ChunkItem::index`].
+/// The positions are updated when the orb entities are batched, so the orbs keep their drift.
+#[derive(Component)]
+pub struct ChunkOrbs {
+    pub chunk_coords: IVec2,
+    pub orbs: Vec<OrbSpawn>,
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkLod {
+    /// Every orb is a separate [`GasOrb`] entity.
+    Detailed,
+    /// The orbs are merged into [`OrbBatch`] meshes.
+    Batched,
+}
+
+/// A mesh containing the orbs of a far chunk that share a material.
+#[derive(Component)]
+pub struct OrbBatch;
+
+/// Whether a chunk at the given squared distance (in chunks) from the player needs separate orb entities.
+pub fn is_chunk_detailed(config: &WorldGenConfig, distance_squared: i32) -> bool {
+    distance_squared <= config.detail_distance * config.detail_distance * 6 / 5
+}
+
+fn update_chunk_lod(
+    mut cmds: Commands,
+    config: Res<WorldGenConfig>,
+    deltas: Res<ChunkDeltas>,
+    orb_assets: Res<OrbAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    player: Single<&Transform, With<Player>>,
+    mut q_chunks: Query<(Entity, &mut ChunkOrbs, Option<&ChunkLod>)>,
+    q_children: Query<&Children>,
+    q_orbs: Query<(&ChunkItem, &Transform, Option<&BurningGasOrb>)>,
+    q_batches: Query<(), With<OrbBatch>>,
+    time: Res<Time<Physics>>,
+) {
+    let player_chunk_coord = (player.translation.truncate() / config.chunk_size)
+        .floor()
+        .as_ivec2();
+    let now = time.elapsed().as_millis() as u32;
+
+    let is_on_fire = |chunk_entity: Entity| {
+        q_children
+            .get(chunk_entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| q_orbs.get(*child).ok())
+            .any(|(_, _, burning)| {
+                burning.is_some_and(|burning| now.saturating_sub(burning.0) < FIRE_DETAIL_MS)
+            })
+    };
+
+    let mut switches = vec![];
+    for (entity, chunk_orbs, lod) in &q_chunks {
+        let d = player_chunk_coord.distance_squared(chunk_orbs.chunk_coords);
+        let target = if is_chunk_detailed(&config, d) {
+            ChunkLod::Detailed
+        } else {
+            ChunkLod::Batched
+        };
+
+        if lod != Some(&target) && !(target == ChunkLod::Batched && is_on_fire(entity)) {
+            switches.push((target, d, entity));
+        }
+    }
+
+    // the ship can only ignite detailed chunks, so they go first
+    switches.sort_by_key(|(target, d, _)| (*target != ChunkLod::Detailed, *d));
+
+    for (target, _, chunk_entity) in switches.into_iter().take(LOD_SWITCHES_PER_TICK) {
+        let Ok((_, mut chunk_orbs, _)) = q_chunks.get_mut(chunk_entity) else {
+            continue;
+        };
+        let chunk_coords = chunk_orbs.chunk_coords;
+
+        // despawn the previous representation, the orbs remember where they drifted to
+        for child in q_children.get(chunk_entity).into_iter().flatten() {
+            if let Ok((item, tr, _)) = q_orbs.get(*child)
+                && item.kind == ChunkItemKind::GasOrb
+            {
+                if let Some(orb) = chunk_orbs.orbs.get_mut(item.index as usize) {
+                    orb.pos = tr.translation;
+                }
+                cmds.entity(*child).try_despawn();
+            } else if q_batches.contains(*child) {
+                cmds.entity(*child).try_despawn();
+            }
+        }
+
+        let delta = deltas.get(chunk_coords);
+        let remaining = chunk_orbs.orbs.iter().enumerate().filter_map(|(i, orb)| {
+            let item = ChunkItem {
+                chunk_coords,
+                kind: ChunkItemKind::GasOrb,
+                index: i as u32,
+            };
+            let consumed = delta.is_some_and(|d| d.is_removed(item.kind, item.index));
+            (!consumed).then_some((item, orb))
+        });
+
+        match target {
+            ChunkLod::Detailed => {
+                for (item, orb) in remaining {
+                    cmds.spawn((
+                        GasOrb(orb.mass),
+                        OrbBiome(orb.biome),
+                        orb.kind,
+                        item,
+                        Transform::from_translation(orb.pos)
+                            .with_scale(Vec3::splat(orb_scale(&config, orb))),
+                        ChildOf(chunk_entity),
+                    ));
+                }
+            }
+            ChunkLod::Batched => {
+                let Some(orb_mesh) = meshes.get(&orb_assets.orb_mesh).cloned() else {
+                    continue;
+                };
+
+                let mut batches = HashMap::<(Biome, GasKind), Mesh>::default();
+                for (_, orb) in remaining {
+                    let disc = orb_mesh.clone().transformed_by(
+                        Transform::from_translation(orb.pos)
+                            .with_scale(Vec3::splat(orb_scale(&config, orb))),
+                    );
+
+                    let material = (orb.biome, orb.kind);
+                    match batches.get_mut(&material) {
+                        Some(batch) => {
+                            if let Err(err) = batch.merge(&disc) {
+                                warn!("could not batch gas orb: {err}");
+                            }
+                        }
+                        None => {
+                            batches.insert(material, disc);
+                        }
+                    }
+                }
+
+                for ((biome, kind), batch) in batches {
+                    cmds.spawn((
+                        Name::new(format!("Orb batch {} {}", biome.name(), kind.name())),
+                        OrbBatch,
+                        Mesh3d(meshes.add(batch)),
+                        MeshMaterial3d(orb_assets.orb_material(biome, kind)),
+                        Transform::default(),
+                        ChildOf(chunk_entity),
+                    ));
+                }
+            }
+        }
+
+        cmds.entity(chunk_entity).insert(target);
+    }
+}
+
+fn orb_scale(config: &WorldGenConfig, orb: &OrbSpawn) -> f32 {
+    config.orbs.min_size + config.orbs.scale * orb.mass
+}
@@ -0,0 +1,405 @@
+//! Headless export of the generated world, used to tune the generation without running the game.
+//!
+//! Run with `cargo run -- --gen-map [--seed 42] [--chunks -8,-8,7,7] [--scale 64] [--out worldgen_map] [--config path]`.
+//! This writes `<out>.png`, a heatmap of the gas noise with the generated objects drawn on top,
+//! and `<out>.json`, the number of objects generated in every chunk.
+
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use image::{Rgb, RgbImage};
+use serde::Serialize;
+
+use crate::cli::{ModeArgs, unknown_option};
+
+use super::{
+    GasGenerator, WorldSeed,
+    config::{CONFIG_PATH, WorldGenConfig},
+    generation::generate_chunk,
+    gravity::GravityKind,
+    smoothstep,
+};
+
+const GRID_COLOR: [u8; 3] = [60, 60, 70];
+const GAS_ORB_COLOR: [u8; 3] = [140, 255, 160];
+const RED_ORB_COLOR: [u8; 3] = [255, 40, 40];
+const ASTEROID_COLOR: [u8; 3] = [200, 200, 200];
+const GRAVITY_WELL_COLOR: [u8; 3] = [170, 120, 255];
+const BLACK_HOLE_COLOR: [u8; 3] = [0, 0, 0];
+/// Largest image exported, about 200 MB in memory.
+const MAX_PIXELS: u64 = 8192 * 8192;
+/// Most chunks exported, each one is generated and listed in the summary.
+const MAX_CHUNKS: u64 = 256 * 256;
+/// Below this scale the chunk borders would cover the whole map, they aren't drawn.
+const MIN_GRID_PIXELS_PER_CHUNK: u32 = 8;
+
+/// Command line options of the map export.
+#[derive(Debug)]
+pub struct MapExportArgs {
+    pub seed: WorldSeed,
+    /// Lower left chunk of the exported area.
+    pub min_chunk: IVec2,
+    /// Upper right chunk of the exported area, inclusive.
+    pub max_chunk: IVec2,
+    /// Width of a chunk in the image, in pixels.
+    pub pixels_per_chunk: u32,
+    /// Output path without the extension.
+    pub out: PathBuf,
+    /// World generation config given with `--config`, otherwise the one in the assets is used.
+    pub config: Option<PathBuf>,
+}
+
+impl MapExportArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            seed: WorldSeed::random(),
+            min_chunk: IVec2::splat(-8),
+            max_chunk: IVec2::splat(7),
+            pixels_per_chunk: 64,
+            out: PathBuf::from("worldgen_map"),
+            config: None,
+        };
+
+        let mut args = ModeArgs::new("--gen-map", args);
+        while let Some(option) = args.next_option() {
+            match option.as_str() {
+                "--seed" => parsed.seed = WorldSeed(args.value(&option)?),
+                "--chunks" => {
+                    let value: String = args.value(&option)?;
+                    let coords = value
+                        .split(',')
+                        .map(|c| c.trim().parse::<i32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                        .filter(|c| c.len() == 4)
+                        .ok_or(format!(
+                            "invalid chunk rectangle `{value}`, expected `min_x,min_y,max_x,max_y`"
+                        ))?;
+                    parsed.min_chunk = IVec2::new(coords[0], coords[1]);
+                    parsed.max_chunk = IVec2::new(coords[2], coords[3]);
+                }
+                "--scale" => {
+                    let scale = args.value(&option)?;
+                    if !(1..=1024).contains(&scale) {
+                        return Err(format!("invalid scale `{scale}`, expected 1 to 1024"));
+                    }
+                    parsed.pixels_per_chunk = scale;
+                }
+                "--out" => parsed.out = args.value(&option)?,
+                "--config" => parsed.config = Some(args.value(&option)?),
+                _ => return Err(unknown_option(&option)),
+            }
+        }
+
+        if parsed.min_chunk.cmpgt(parsed.max_chunk).any() {
+            return Err(format!(
+                "empty chunk rectangle, {} is not below and left of {}",
+                parsed.min_chunk, parsed.max_chunk
+            ));
+        }
+        if parsed.chunk_count().is_none_or(|count| count > MAX_CHUNKS) {
+            return Err(format!(
+                "the map from {} to {} has more than {MAX_CHUNKS} chunks, lower `--chunks`",
+                parsed.min_chunk, parsed.max_chunk
+            ));
+        }
+        if parsed.image_size().is_none() {
+            return Err(format!(
+                "the map from {} to {} at {} pixels per chunk is too large, lower `--chunks` or `--scale`",
+                parsed.min_chunk, parsed.max_chunk, parsed.pixels_per_chunk
+            ));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Number of chunks in the rectangle, `None` if it doesn't fit in a `u64`.
+    pub fn chunk_count(&self) -> Option<u64> {
+        let side = |min: i32, max: i32| u64::try_from(i64::from(max) - i64::from(min) + 1).ok();
+        side(self.min_chunk.x, self.max_chunk.x)?
+            .checked_mul(side(self.min_chunk.y, self.max_chunk.y)?)
+    }
+
+    /// Size of the image in pixels, `None` for an empty rectangle or more than [`MAX_PIXELS`].
+    pub fn image_size(&self) -> Option<UVec2> {
+        let side = |min: i32, max: i32| {
+            let chunks = (i64::from(max) - i64::from(min)).checked_add(1)?;
+            let pixels = chunks.checked_mul(i64::from(self.pixels_per_chunk))?;
+            u32::try_from(pixels).ok().filter(|&p| p > 0)
+        };
+        let size = UVec2::new(
+            side(self.min_chunk.x, self.max_chunk.x)?,
+            side(self.min_chunk.y, self.max_chunk.y)?,
+        );
+        (u64::from(size.x) * u64::from(size.y) <= MAX_PIXELS).then_some(size)
+    }
+}
+
+#[derive(Serialize)]
+pub struct MapSummary {
+    pub seed: u32,
+    pub chunk_size: f32,
+    pub totals: MapTotals,
+    pub chunks: Vec<ChunkSummary>,
+}
+
+#[derive(Serialize, Default)]
+pub struct MapTotals {
+    pub gas_orbs: usize,
+    pub red_orbs: usize,
+    pub asteroids: usize,
+    pub gravity_wells: usize,
+    pub black_holes: usize,
+}
+
+#[derive(Serialize)]
+pub struct ChunkSummary {
+    pub x: i32,
+    pub y: i32,
+    /// The biome with the highest weight in the center of the chunk.
+    pub biome: &'static str,
+    pub gas_orbs: usize,
+    pub red_orbs: usize,
+    pub asteroids: usize,
+    /// The gravity well or black hole of the chunk, if any.
+    pub gravity: Option<&'static str>,
+}
+
+/// Writes the map and the summary of `--gen-map`, the errors are printed.
+/// `args` is the command line without the program name.
+pub fn run(args: impl IntoIterator<Item = String>) -> AppExit {
+    match MapExportArgs::parse(args).and_then(|args| export_map(&args)) {
+        Ok(()) => AppExit::Success,
+        Err(err) => {
+            eprintln!("map export failed: {err}");
+            AppExit::error()
+        }
+    }
+}
+
+pub fn export_map(args: &MapExportArgs) -> Result<(), String> {
+    let config = match &args.config {
+        // a config asked for by name must be the one used
+        Some(path) => {
+            let bytes = fs::read(path)
+                .map_err(|err| format!("could not read {}: {err}", path.display()))?;
+            WorldGenConfig::from_ron(&bytes).map_err(|err| err.to_string())?
+        }
+        None => {
+            let path = PathBuf::from("assets").join(CONFIG_PATH);
+            match fs::read(&path) {
+                Ok(bytes) => WorldGenConfig::from_ron(&bytes).map_err(|err| err.to_string())?,
+                Err(err) => {
+                    eprintln!(
+                        "could not read {}: {err}, using the default config",
+                        path.display()
+                    );
+                    WorldGenConfig::default()
+                }
+            }
+        }
+    };
+
+    let (image, summary) = render_map(args, &config);
+
+    let png_path = args.out.with_extension("png");
+    image
+        .save(&png_path)
+        .map_err(|err| format!("could not write {}: {err}", png_path.display()))?;
+
+    let json_path = args.out.with_extension("json");
+    let json = serde_json::to_string_pretty(&summary).map_err(|err| err.to_string())?;
+    fs::write(&json_path, json)
+        .map_err(|err| format!("could not write {}: {err}", json_path.display()))?;
+
+    println!(
+        "seed {}: {} gas orbs, {} red orbs, {} asteroids, {} gravity wells, {} black holes in {} chunks, written to {} and {}",
+        summary.seed,
+        summary.totals.gas_orbs,
+        summary.totals.red_orbs,
+        summary.totals.asteroids,
+        summary.totals.gravity_wells,
+        summary.totals.black_holes,
+        summary.chunks.len(),
+        png_path.display(),
+        json_path.display()
+    );
+
+    Ok(())
+}
+
+/// Generates the chunks in the rectangle and draws them.
+pub fn render_map(args: &MapExportArgs, config: &WorldGenConfig) -> (RgbImage, MapSummary) {
+    let gas = GasGenerator::new(args.seed, config);
+    let chunk_size = config.chunk_size;
+    let ppc = args.pixels_per_chunk;
+    let size = args
+        .image_size()
+        .expect("the map size is checked when parsing the arguments");
+
+    let mut image = RgbImage::new(size.x, size.y);
+
+    // world position of the top left corner of the image, the image y axis points down
+    let origin = Vec2::new(args.min_chunk.x as f32, args.max_chunk.y as f32 + 1.0) * chunk_size;
+    let pixel_size = chunk_size / ppc as f32;
+    let grid = ppc >= MIN_GRID_PIXELS_PER_CHUNK;
+    let to_pixel = |p: Vec2| Vec2::new(p.x - origin.x, origin.y - p.y) / pixel_size;
+
+    for (px, py, pixel) in image.enumerate_pixels_mut() {
+        let p = origin + Vec2::new(px as f32 + 0.5, -(py as f32 + 0.5)) * pixel_size;
+        *pixel = if grid && (px % ppc == 0 || py % ppc == 0) {
+            Rgb(GRID_COLOR)
+        } else {
+            Rgb(heat_color(gas.sample(p)))
+        };
+    }
+
+    let mut summary = MapSummary {
+        seed: args.seed.0,
+        chunk_size,
+        totals: MapTotals::default(),
+        chunks: vec![],
+    };
+
+    for y in args.min_chunk.y..=args.max_chunk.y {
+        for x in args.min_chunk.x..=args.max_chunk.x {
+            let chunk_coords = IVec2::new(x, y);
+            let content = generate_chunk(&gas, config, args.seed, chunk_coords);
+
+            for orb in &content.orbs {
+                draw_disc(&mut image, to_pixel(orb.pos.truncate()), 0.0, GAS_ORB_COLOR);
+            }
+            for red_orb in &content.red_orbs {
+                let radius = red_orb.radius / pixel_size;
+                draw_disc(
+                    &mut image,
+                    to_pixel(red_orb.pos.truncate()),
+                    radius,
+                    RED_ORB_COLOR,
+                );
+            }
+            for asteroid in &content.asteroids {
+                let radius = asteroid.radius / pixel_size;
+                draw_disc(
+                    &mut image,
+                    to_pixel(asteroid.pos.truncate()),
+                    radius,
+                    ASTEROID_COLOR,
+                );
+            }
+            for body in &content.gravity_bodies {
+                let (radius, color) = match body.kind {
+                    GravityKind::Well => (body.horizon_radius * 2.0, GRAVITY_WELL_COLOR),
+                    GravityKind::BlackHole => (body.horizon_radius, BLACK_HOLE_COLOR),
+                };
+                draw_disc(
+                    &mut image,
+                    to_pixel(body.pos_2d()),
+                    radius / pixel_size,
+                    color,
+                );
+            }
+
+            let center = (chunk_coords.as_vec2() + 0.5) * chunk_size;
+            let chunk = ChunkSummary {
+                x,
+                y,
+                biome: gas.biome_weights(center).dominant().name(),
+                gas_orbs: content.orbs.len(),
+                red_orbs: content.red_orbs.len(),
+                asteroids: content.asteroids.len(),
+                gravity: content.gravity_bodies.first().map(|body| body.kind.name()),
+            };
+
+            summary.totals.gas_orbs += chunk.gas_orbs;
+            summary.totals.red_orbs += chunk.red_orbs;
+            summary.totals.asteroids += chunk.asteroids;
+            for body in &content.gravity_bodies {
+                match body.kind {
+                    GravityKind::Well => summary.totals.gravity_wells += 1,
+                    GravityKind::BlackHole => summary.totals.black_holes += 1,
+                }
+            }
+            summary.chunks.push(chunk);
+        }
+    }
+
+    (image, summary)
+}
+
+/// Maps a gas noise sample to a dark blue - purple - orange ramp.
+fn heat_color(r: f32) -> [u8; 3] {
+    const LOW: Vec3 = Vec3::new(10.0, 10.0, 30.0);
+    const MID: Vec3 = Vec3::new(110.0, 40.0, 130.0);
+    const HIGH: Vec3 = Vec3::new(250.0, 170.0, 60.0);
+
+    let t = smoothstep(-0.5, 0.5, r);
+    let c = if t < 0.5 {
+        LOW.lerp(MID, t * 2.0)
+    } else {
+        MID.lerp(HIGH, t * 2.0 - 1.0)
+    };
+
+    [c.x as u8, c.y as u8, c.z as u8]
+}
+
+/// Fills a disc, anything smaller than a pixel is drawn as a single pixel.
+fn draw_disc(image: &mut RgbImage, center: Vec2, radius: f32, color: [u8; 3]) {
+    if radius < 1.0 {
+        let (x, y) = (center.x.floor(), center.y.floor());
+        if x >= 0.0 && y >= 0.0 && x < image.width() as f32 && y < image.height() as f32 {
+            image.put_pixel(x as u32, y as u32, Rgb(color));
+        }
+        return;
+    }
+
+    let min = (center - radius).floor().max(Vec2::ZERO);
+    let max = (center + radius)
+        .ceil()
+        .min(Vec2::new(image.width() as f32, image.height() as f32));
+
+    for y in min.y as u32..max.y as u32 {
+        for x in min.x as u32..max.x as u32 {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            if p.distance_squared(center) <= radius * radius {
+                image.put_pixel(x, y, Rgb(color));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<MapExportArgs, String> {
+        MapExportArgs::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn rejects_reversed_rectangles() {
+        assert!(parse("--chunks 0,0,0,0").is_ok());
+        assert!(parse("--chunks 1,0,0,0").is_err());
+        assert!(parse("--chunks 0,0,5,-5").is_err());
+    }
+
+    #[test]
+    fn rejects_huge_maps() {
+        let size = parse("--chunks -8,-8,7,7 --scale 64").unwrap().image_size();
+        assert_eq!(size, Some(UVec2::splat(1024)));
+
+        assert!(parse("--chunks -2147483648,0,2147483647,0 --scale 1").is_err());
+        assert!(parse("--chunks -1000,-1000,1000,1000 --scale 1024").is_err());
+        assert!(parse("--chunks 0,0,255,255 --scale 32").is_ok());
+        assert!(parse("--chunks 0,0,255,255 --scale 33").is_err());
+    }
+
+    #[test]
+    fn rejects_too_many_chunks() {
+        let args = parse("--chunks 0,0,255,255 --scale 1").unwrap();
+        assert_eq!(args.chunk_count(), Some(MAX_CHUNKS));
+        assert!(parse("--chunks 0,0,256,255 --scale 1").is_err());
+        // small enough as an image, still too much to generate
+        assert!(parse("--chunks 0,0,8191,8191 --scale 1").is_err());
+    }
+}
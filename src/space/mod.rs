@@ -1,56 +1,67 @@
 //!
 //! This handles placement of the orbs in the level according to an underlying simplex noise function.
-//! To ensure the space is infinite it's made up of square chunks `CHUNK_SIZE` units wide,
+//! To ensure the space is infinite it's made up of square chunks `chunk_size` units wide,
 //! that are populated on the fly as the player moves around.
+//! The generation parameters come from [`config::WorldGenConfig`].
 //!
 
 use avian2d::parry::utils::hashmap::HashMap;
 use bevy::prelude::*;
-use gas::GasOrb;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use biome::BiomeWeights;
+use config::WorldGenConfig;
+use delta::{ChunkDeltas, ChunkItem, ChunkItemKind};
+use gas::kind::GasKind;
+use generation::{ChunkContent, generate_chunk};
+use lod::ChunkOrbs;
 use noiz::{Noise, SampleableFor, prelude::common_noise::Perlin, rng::NoiseRng};
+use rand::{SeedableRng, rngs::SmallRng};
 
-use crate::{asteroids::Asteroid, player::Player, red_gas::RedGasOrb, screens::Screen};
+use crate::{PausableSystems, asteroids::Asteroid, player::Player, screens::Screen};
 
+pub mod biome;
+pub mod config;
+pub mod delta;
 pub mod gas;
+pub mod generation;
+pub mod gravity;
 pub mod intro;
+pub mod lod;
+pub mod map_export;
 
 pub fn plugin(app: &mut App) {
     app.add_plugins(intro::plugin);
 
-    app.add_plugins(gas::plugin)
-        .insert_resource(GasGenerator {
-            noise: Noise {
-                noise: Perlin::default(),
-                seed: NoiseRng(rand::random()),
-                frequency: 0.004,
-            },
-        })
-        .insert_resource(PopulatedChunks::default())
-        .add_observer(populate_chunk)
-        .add_systems(
-            FixedUpdate,
-            (trigger_chunk_population, unload_far_chunks).chain(),
-        );
+    let seed = WorldSeed::random();
+
+    app.add_plugins((
+        gas::plugin,
+        biome::plugin,
+        config::plugin,
+        delta::plugin,
+        gravity::plugin,
+        lod::plugin,
+    ))
+    .insert_resource(seed)
+    .insert_resource(GasGenerator::new(seed, &WorldGenConfig::default()))
+    .insert_resource(PopulatedChunks::default())
+    .add_observer(populate_chunk)
+    .add_systems(OnEnter(Screen::Title), reroll_world_seed)
+    .add_systems(OnEnter(Screen::Gameplay), reset_world)
+    .add_systems(
+        FixedUpdate,
+        (
+            trigger_chunk_population,
+            unload_far_chunks,
+            (commit_chunks, rehome_asteroids)
+                .chain()
+                .run_if(in_state(Screen::Gameplay))
+                .in_set(PausableSystems),
+        )
+            .chain(),
+    );
 }
 
-pub const CHUNK_SIZE: f32 = 256.0;
-/// Number of orbs per m²
-pub const MAX_CLOUD_DENSITY: f32 = 0.018;
-pub const RENDER_DISTANCE: i32 = 3;
-pub const ORB_THRESHOLD: f32 = 0.14;
-
-const MIN_ASTEROID_SIZE: f32 = 20.0;
-const ASTEROID_SIZE_VARIATION: f32 = 25.0;
-const ASTEROID_CLOUD_Z_SCALE: f32 = 10.0;
-
-const MIN_EXPLOSIVE_ORB_SIZE: f32 = 2.0;
-const EXPLOSIVE_ORB_SIZE_VARIATION: f32 = 5.0;
-const EXPLOSIVE_ORB_CLOUD_Z_SCALE: f32 = 70.0;
-
-pub const MIN_ORB_SIZE: f32 = 0.4;
-pub const ORB_SCALE: f32 = 4.4;
-pub const CLOUD_Z_SCALE: f32 = 80.0;
-
 pub const INTRO_SCENE_RADIUS: f32 = 2000.;
 pub const INTRO_SCENE_RADIUS_SQ: f32 = INTRO_SCENE_RADIUS * INTRO_SCENE_RADIUS;
 
@@ -58,9 +69,58 @@ pub const INTRO_SCENE_RADIUS_SQ: f32 = INTRO_SCENE_RADIUS * INTRO_SCENE_RADIUS;
 #[derive(Default, Resource)]
 pub struct PopulatedChunks(HashMap<IVec2, Entity>);
 
-#[derive(Resource, Default)]
+impl PopulatedChunks {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Seed of the whole world. Two runs with the same seed have the same layout,
+/// so players can share them and testers can reproduce them.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldSeed(pub u32);
+
+impl WorldSeed {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// RNG used to scatter the contents of a single chunk.
+    /// It only depends on the seed and the chunk coordinates, so a chunk regenerates
+    /// identically regardless of the order the chunks were visited in.
+    pub fn chunk_rng(&self, chunk_coords: IVec2) -> SmallRng {
+        self.salted_chunk_rng(0, chunk_coords)
+    }
+
+    /// RNG placing the rare features of a chunk, like gravity wells.
+    /// It's separate from [`Self::chunk_rng`] so adding features doesn't reshuffle the rest of the chunk.
+    pub fn feature_rng(&self, chunk_coords: IVec2) -> SmallRng {
+        self.salted_chunk_rng(0xfea7_u64 << 32, chunk_coords)
+    }
+
+    fn salted_chunk_rng(&self, salt: u64, chunk_coords: IVec2) -> SmallRng {
+        let coords = ((chunk_coords.x as u32 as u64) << 32) | chunk_coords.y as u32 as u64;
+        SmallRng::seed_from_u64(splitmix64(splitmix64(self.0 as u64 ^ salt) ^ coords))
+    }
+}
+
+/// A cheap 64 bit mixing function, so neighbouring chunks don't get correlated seeds.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Resource, Default, Clone)]
 pub struct GasGenerator {
     noise: Noise<Perlin>,
+    /// Low frequency noise selecting the biomes.
+    biome_noise: Noise<Perlin>,
 }
 
 pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
@@ -69,6 +129,57 @@ pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
 }
 
 impl GasGenerator {
+    pub fn new(seed: WorldSeed, config: &WorldGenConfig) -> Self {
+        Self {
+            noise: Noise {
+                noise: Perlin::default(),
+                seed: NoiseRng(seed.0),
+                frequency: config.noise_frequency,
+            },
+            biome_noise: Noise {
+                noise: Perlin::default(),
+                seed: NoiseRng(seed.0 ^ 0xb10e_b10e),
+                frequency: config.biome_noise_frequency,
+            },
+        }
+    }
+
+    /// Weights of the biomes at the given point.
+    /// The intro region always fades into the default biome, so every run starts the same way.
+    pub fn biome_weights(&self, p: Vec2) -> BiomeWeights {
+        let weights = BiomeWeights::from_sample(Vec2::new(
+            SampleableFor::<Vec2, f32>::sample(&self.biome_noise, p),
+            SampleableFor::<Vec2, f32>::sample(&self.biome_noise, p + 5000.0),
+        ));
+
+        let outside_intro = smoothstep(
+            INTRO_SCENE_RADIUS_SQ,
+            4.0 * INTRO_SCENE_RADIUS_SQ,
+            p.length_squared(),
+        );
+        BiomeWeights::default().mix(&weights, outside_intro)
+    }
+
+    /// Kind of the gas at the given point. Patches of special gas are scattered outside the intro region.
+    pub fn gas_kind(&self, p: Vec2) -> GasKind {
+        if p.length_squared() < INTRO_SCENE_RADIUS_SQ {
+            return GasKind::Standard;
+        }
+
+        let a = SampleableFor::<Vec2, f32>::sample(&self.noise, p * 0.5 + 3000.0);
+        let b = SampleableFor::<Vec2, f32>::sample(&self.noise, p * 0.5 - 3000.0);
+
+        if a > 0.3 {
+            GasKind::Volatile
+        } else if a < -0.3 {
+            GasKind::Coolant
+        } else if b > 0.35 {
+            GasKind::Ionized
+        } else {
+            GasKind::Standard
+        }
+    }
+
     pub fn sample(&self, p: Vec2) -> f32 {
         let offset: Vec2 = Vec2::new(
             self.noise.sample(p * 2.0 + 100.0),
@@ -94,200 +205,325 @@ impl GasGenerator {
     // }
 }
 
-/// Populate new chunks
+/// Every visit of the title screen offers a new world.
+pub fn reroll_world_seed(mut seed: ResMut<WorldSeed>) {
+    *seed = WorldSeed::random();
+}
+
+/// Rebuilds the generator from the current seed and forgets the chunks of the previous run.
+pub fn reset_world(
+    seed: Res<WorldSeed>,
+    config: Res<WorldGenConfig>,
+    mut gas: ResMut<GasGenerator>,
+    mut populated: ResMut<PopulatedChunks>,
+    mut deltas: ResMut<ChunkDeltas>,
+) {
+    info!("world seed: {}", seed.0);
+    *gas = GasGenerator::new(*seed, &config);
+    populated.0.clear();
+    deltas.0.clear();
+}
+
+/// Queue generation of new chunks.
+/// Every missing chunk around the player gets an entity right away, while its content
+/// is computed on the [`AsyncComputeTaskPool`] and committed [`CHUNK_COMMIT_DELAY_TICKS`] later by [`commit_chunks`].
 fn trigger_chunk_population(
     mut cmds: Commands,
-    populated: Res<PopulatedChunks>,
+    mut populated: ResMut<PopulatedChunks>,
+    gas: Res<GasGenerator>,
+    config: Res<WorldGenConfig>,
+    seed: Res<WorldSeed>,
     q_player: Single<&Transform, With<Player>>,
 ) {
     let player_tr = q_player.into_inner();
 
     let player_tr_2d = player_tr.translation.truncate();
 
-    let player_chunk_coord = (player_tr_2d / CHUNK_SIZE).floor().as_ivec2();
-
-    let mut closest = None;
-    let mut min_d = i32::MAX;
+    let player_chunk_coord = (player_tr_2d / config.chunk_size).floor().as_ivec2();
 
-    let r = RENDER_DISTANCE;
+    let r = config.render_distance;
 
-    // todo: more efficient traversal and quit on first match
+    let mut missing = vec![];
     for y in -r..=r {
         for x in -r..=r {
             let chunk_coords = player_chunk_coord + IVec2::new(x, y);
+            let d = IVec2::new(x, y).length_squared();
 
-            if !populated.0.contains_key(&chunk_coords) {
-                // let pos = chunk_coords.as_vec2().extend(0.0) * CHUNK_SIZE;
-                // let chunk_entity = cmds
-                //     .spawn((
-                //         Transform::from_translation(pos),
-                //         InheritedVisibility::VISIBLE,
-                //     ))
-                //     .id();
-                // cmds.trigger_targets(PopulateChunk(chunk_coords), chunk_entity);
-
-                let d = IVec2::new(x, y).length_squared();
-
-                if d < min_d {
-                    closest = Some(chunk_coords);
-                    min_d = d;
-                }
+            // corner chunks would be unloaded right away by `unload_far_chunks`
+            if is_chunk_in_range(&config, d) && !populated.0.contains_key(&chunk_coords) {
+                missing.push((d, chunk_coords));
             }
         }
     }
 
-    if let Some(chunk_coords) = closest {
-        // let pos = chunk_coords.as_vec2().extend(0.0) * CHUNK_SIZE;
+    // closest chunks are queued first, so they are more likely to be ready first
+    missing.sort_by_key(|(d, _)| *d);
+
+    let task_pool = AsyncComputeTaskPool::get();
+    for (_, chunk_coords) in missing {
+        let gas = gas.clone();
+        let config = config.clone();
+        let seed = *seed;
+        let task =
+            task_pool.spawn(async move { generate_chunk(&gas, &config, seed, chunk_coords) });
+
+        // The chunk is considered populated as soon as its task is spawned.
+        // If it gets unloaded before the task finishes, despawning the entity drops (and cancels) the task.
         let chunk_entity = cmds
             .spawn((
+                Name::new(format!("Chunk {chunk_coords}")),
                 DespawnOnExit(Screen::Gameplay),
                 Transform::default(),
                 InheritedVisibility::VISIBLE,
+                ChunkGenTask {
+                    chunk_coords,
+                    task,
+                    ticks_left: CHUNK_COMMIT_DELAY_TICKS,
+                },
             ))
             .id();
-        // cmds.trigger_targets(PopulateChunk(chunk_coords), chunk_entity);
-        cmds.trigger(PopulateChunk {
-            chunk_coords,
-            entity: chunk_entity,
-        })
+
+        populated.0.insert(chunk_coords, chunk_entity);
     }
 }
 
-#[derive(Debug, EntityEvent)]
-pub struct PopulateChunk {
-    entity: Entity,
+/// Fixed ticks between queuing a chunk and committing its content.
+/// The task is almost always done by then, otherwise the commit waits for it.
+/// Either way the chunk appears on the same tick, so runs with the same input play out the same.
+const CHUNK_COMMIT_DELAY_TICKS: u32 = 8;
+
+/// Maximum number of generated chunks committed to the world per tick.
+const CHUNK_COMMITS_PER_TICK: usize = 2;
+
+/// Background generation of a chunk's content.
+#[derive(Component)]
+pub struct ChunkGenTask {
     chunk_coords: IVec2,
+    task: Task<ChunkContent>,
+    ticks_left: u32,
 }
 
-fn asteroid_distribution(r: f32) -> f32 {
-    let a = smoothstep(-0.5, -0.3, r);
-    let b = smoothstep(-0.1, -0.3, r);
+/// Commit the content of the chunks that are due, at most [`CHUNK_COMMITS_PER_TICK`] per tick.
+/// The ones over the limit are committed on the next ticks.
+fn commit_chunks(mut cmds: Commands, mut q_tasks: Query<(Entity, &mut ChunkGenTask)>) {
+    let mut committed = 0;
+
+    for (entity, mut gen_task) in &mut q_tasks {
+        gen_task.ticks_left = gen_task.ticks_left.saturating_sub(1);
+        if gen_task.ticks_left > 0 || committed >= CHUNK_COMMITS_PER_TICK {
+            continue;
+        }
+
+        let Some(content) = finish_task(&mut gen_task.task) else {
+            continue;
+        };
 
-    a.min(b)
+        cmds.entity(entity).remove::<ChunkGenTask>();
+        cmds.trigger(PopulateChunk {
+            entity,
+            chunk_coords: gen_task.chunk_coords,
+            content,
+        });
+        committed += 1;
+    }
 }
 
-fn explosive_orb_distribution(r: f32) -> f32 {
-    let a = smoothstep(0.3, 0.35, r);
-    let b = smoothstep(0.5, 0.35, r);
+/// Waits for the generation to finish.
+/// Nothing can block on the web, there a late chunk is committed on a later tick instead.
+fn finish_task(task: &mut Task<ChunkContent>) -> Option<ChunkContent> {
+    #[cfg(not(target_family = "wasm"))]
+    return Some(bevy::tasks::block_on(task));
 
-    a.min(b)
+    #[cfg(target_family = "wasm")]
+    bevy::tasks::futures::check_ready(task)
 }
 
-/// Observer that populates a chunk with orb clouds.
-/// The chunk is first subdivided into `CHUNK_SUBDIV` parts along each axis,
-/// then each cell may spawn an orb depending on randomness and underlying space parameters.
+#[derive(EntityEvent)]
+pub struct PopulateChunk {
+    entity: Entity,
+    chunk_coords: IVec2,
+    content: ChunkContent,
+}
+
+/// Observer that spawns the generated content of a chunk as children of the chunk entity.
+/// Objects the player destroyed on a previous visit are skipped, so are the asteroids
+/// that drifted into another loaded chunk.
 fn populate_chunk(
     trigger: On<PopulateChunk>,
     mut cmds: Commands,
-    gas: Res<GasGenerator>,
-    mut populated: ResMut<PopulatedChunks>,
+    deltas: Res<ChunkDeltas>,
+    q_asteroid_items: Query<&ChunkItem, With<Asteroid>>,
 ) {
-    // Calculate how many subdivisions along each axis is required to get the desired maximum cloud density.
-    const CHUNK_SUBDIV: usize = ((MAX_CLOUD_DENSITY * CHUNK_SIZE * CHUNK_SIZE) as usize).isqrt();
-
-    for y in 0..CHUNK_SUBDIV {
-        for x in 0..CHUNK_SUBDIV {
-            let cell_pos = trigger.event().chunk_coords.as_vec2() * CHUNK_SIZE
-                + (Vec2::new(x as f32, y as f32) / (CHUNK_SUBDIV as f32)) * CHUNK_SIZE;
-            let r = gas.sample(cell_pos);
-
-            // println!("start_mask: {start_mask}");
-
-            if r > ORB_THRESHOLD {
-                // The actual orb position is slightly offset to avoid a grid-like look
-                // TODO: improve random generation perf
-                let pos = cell_pos
-                    + Vec2::new(rand::random::<f32>(), rand::random::<f32>()) * CHUNK_SIZE
-                        / CHUNK_SUBDIV as f32;
-
-                cmds.spawn((
-                    GasOrb(r),
-                    Transform::from_translation(
-                        pos.extend((rand::random::<f32>() - 0.5) * CLOUD_Z_SCALE * r),
-                    ) // todo: we can vary that 0.5 with another noise for more depth effect
-                    .with_scale(Vec3::splat(MIN_ORB_SIZE + ORB_SCALE * r)),
-                    ChildOf(trigger.event().event_target()),
-                ));
-            }
+    let chunk_entity = trigger.event().event_target();
+    let chunk_coords = trigger.event().chunk_coords;
+    let content = &trigger.event().content;
+    let delta = deltas.get(chunk_coords);
+
+    let chunk_item = |kind, index: usize| ChunkItem {
+        chunk_coords,
+        kind,
+        index: index as u32,
+    };
+    let is_removed = |item: &ChunkItem| delta.is_some_and(|d| d.is_removed(item.kind, item.index));
+
+    debug!(
+        "populating chunk {}: {} orbs, {} red orbs, {} asteroids, {} gravity bodies",
+        chunk_coords,
+        content.orbs.len(),
+        content.red_orbs.len(),
+        content.asteroids.len(),
+        content.gravity_bodies.len()
+    );
+
+    // gas orbs are spawned by `lod::update_chunk_lod` depending on the distance to the ship
+    cmds.entity(chunk_entity).insert(ChunkOrbs {
+        chunk_coords,
+        orbs: content.orbs.clone(),
+    });
+
+    for (i, red_orb) in content.red_orbs.iter().enumerate() {
+        let item = chunk_item(ChunkItemKind::RedGasOrb, i);
+        if !is_removed(&item) {
+            cmds.spawn((red_orb.clone(), item, ChildOf(chunk_entity)));
+        }
+    }
 
-            let explosive_orb_r = explosive_orb_distribution(r);
-
-            if explosive_orb_r > 0.70 {
-                if rand::random::<f32>() < 0.99 {
-                    continue;
-                }
-
-                let pos = cell_pos;
-
-                let r = rand::random::<f32>();
-                let orb_size = MIN_EXPLOSIVE_ORB_SIZE + EXPLOSIVE_ORB_SIZE_VARIATION * r;
-                cmds.spawn((
-                    RedGasOrb {
-                        pos: pos
-                            .extend((rand::random::<f32>() - 0.5) * EXPLOSIVE_ORB_CLOUD_Z_SCALE),
-                        radius: orb_size,
-                    },
-                    ChildOf(trigger.event().event_target()),
-                ));
-            }
+    let drifted_away: Vec<u32> = q_asteroid_items
+        .iter()
+        .filter(|item| item.chunk_coords == chunk_coords)
+        .map(|item| item.index)
+        .collect();
+    for (i, asteroid) in content.asteroids.iter().enumerate() {
+        let item = chunk_item(ChunkItemKind::Asteroid, i);
+        if !is_removed(&item) && !drifted_away.contains(&item.index) {
+            cmds.spawn((asteroid.clone(), item, ChildOf(chunk_entity)));
         }
+    }
 
-        const ASTEROID_SPAWN_SCALE: f32 = 0.2;
-
-        const ASTEROID_CHUNK_SUBDIV: u32 = ((CHUNK_SUBDIV as f32) * ASTEROID_SPAWN_SCALE) as u32;
-        for y in 0..ASTEROID_CHUNK_SUBDIV {
-            for x in 0..ASTEROID_CHUNK_SUBDIV {
-                let cell_pos = trigger.event().chunk_coords.as_vec2() * CHUNK_SIZE
-                    + (Vec2::new(x as f32, y as f32) / (ASTEROID_CHUNK_SUBDIV as f32)) * CHUNK_SIZE;
-
-                let r = gas.sample(cell_pos);
-
-                let is_intro_region = cell_pos.length_squared() < INTRO_SCENE_RADIUS_SQ;
-                let meteorite_r = asteroid_distribution(r);
-                if meteorite_r > 0.60 && !is_intro_region {
-                    if rand::random::<f32>() < 0.99 {
-                        continue;
-                    }
-                    let pos = cell_pos
-                        + Vec2::new(rand::random::<f32>(), rand::random::<f32>()) * CHUNK_SIZE
-                            / CHUNK_SUBDIV as f32;
-
-                    let r = rand::random::<f32>();
-                    let asteroid_size = MIN_ASTEROID_SIZE + ASTEROID_SIZE_VARIATION * r;
-                    cmds.spawn((
-                        Asteroid {
-                            pos: pos.extend((rand::random::<f32>() - 0.5) * ASTEROID_CLOUD_Z_SCALE),
-                            radius: asteroid_size,
-                        },
-                        ChildOf(trigger.event().event_target()),
-                    ));
-                }
+    // gravity bodies can't be destroyed, so they aren't tracked by the deltas
+    for body in &content.gravity_bodies {
+        cmds.spawn((body.clone(), ChildOf(chunk_entity)));
+    }
+}
+
+/// Moves the drifting asteroids to the chunk they are in, so they are unloaded along with it
+/// rather than with the chunk they were generated in. The ones leaving the loaded chunks are unloaded.
+fn rehome_asteroids(
+    mut cmds: Commands,
+    populated: Res<PopulatedChunks>,
+    config: Res<WorldGenConfig>,
+    q_asteroids: Query<(Entity, &Transform, &ChildOf), With<Asteroid>>,
+) {
+    for (entity, tr, parent) in &q_asteroids {
+        let chunk_coords = (tr.translation.truncate() / config.chunk_size)
+            .floor()
+            .as_ivec2();
+        match populated.0.get(&chunk_coords) {
+            Some(chunk_entity) if *chunk_entity == parent.parent() => {}
+            // chunks sit at the origin, the asteroid keeps its transform
+            Some(chunk_entity) => {
+                cmds.entity(entity).insert(ChildOf(*chunk_entity));
             }
+            None => cmds.entity(entity).despawn(),
         }
     }
+}
 
-    // debug!("chunk generation took {t:.2?}");
-
-    populated
-        .0
-        .insert(trigger.event().chunk_coords, trigger.event().event_target());
+/// Whether a chunk at the given squared distance (in chunks) from the player should be loaded.
+fn is_chunk_in_range(config: &WorldGenConfig, distance_squared: i32) -> bool {
+    // need to figure out this const
+    distance_squared <= config.render_distance * config.render_distance * 6 / 5
 }
 
 fn unload_far_chunks(
     mut cmds: Commands,
     mut populated: ResMut<PopulatedChunks>,
+    config: Res<WorldGenConfig>,
     player: Single<&Transform, With<Player>>,
 ) {
-    let player_chunk_coord = (player.translation.truncate() / CHUNK_SIZE)
+    let player_chunk_coord = (player.translation.truncate() / config.chunk_size)
         .floor()
         .as_ivec2();
     for (chunk_coords, chunk_entity) in populated.0.clone().iter() {
-        // need to figure out this const
-        if player_chunk_coord.distance_squared(*chunk_coords)
-            > RENDER_DISTANCE * RENDER_DISTANCE * 6 / 5
-        {
+        if !is_chunk_in_range(&config, player_chunk_coord.distance_squared(*chunk_coords)) {
             populated.0.remove(chunk_coords);
             cmds.entity(*chunk_entity).try_despawn();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::red_gas::RedGasOrb;
+
+    fn red_orb(x: f32) -> RedGasOrb {
+        RedGasOrb {
+            radius: 10.0,
+            pos: Vec3::new(x, 0.0, 0.0),
+        }
+    }
+
+    fn asteroid(x: f32) -> Asteroid {
+        Asteroid {
+            pos: Vec3::new(x, 100.0, 0.0),
+            radius: 10.0,
+            velocity: Vec2::ZERO,
+            spin: 0.0,
+        }
+    }
+
+    /// The items spawned in the chunk, by kind, in index order.
+    fn spawned(app: &mut App, kind: ChunkItemKind) -> Vec<u32> {
+        let mut indices: Vec<u32> = app
+            .world_mut()
+            .query::<&ChunkItem>()
+            .iter(app.world())
+            .filter(|item| item.kind == kind)
+            .map(|item| item.index)
+            .collect();
+        indices.sort();
+        indices
+    }
+
+    #[test]
+    fn reloaded_chunk_leaves_out_the_destroyed_items() {
+        let chunk_coords = IVec2::new(3, -1);
+        let mut app = App::new();
+        app.init_resource::<ChunkDeltas>()
+            .add_observer(populate_chunk);
+
+        let mut deltas = app.world_mut().resource_mut::<ChunkDeltas>();
+        deltas.record(&ChunkItem {
+            chunk_coords,
+            kind: ChunkItemKind::RedGasOrb,
+            index: 1,
+        });
+        deltas.record(&ChunkItem {
+            chunk_coords,
+            kind: ChunkItemKind::Asteroid,
+            index: 0,
+        });
+        // the same index in another chunk doesn't matter
+        deltas.record(&ChunkItem {
+            chunk_coords: IVec2::ZERO,
+            kind: ChunkItemKind::Asteroid,
+            index: 2,
+        });
+
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().trigger(PopulateChunk {
+            entity,
+            chunk_coords,
+            content: ChunkContent {
+                red_orbs: vec![red_orb(0.0), red_orb(50.0), red_orb(100.0)],
+                asteroids: vec![asteroid(0.0), asteroid(50.0), asteroid(100.0)],
+                ..default()
+            },
+        });
+        app.world_mut().flush();
+
+        assert_eq!(spawned(&mut app, ChunkItemKind::RedGasOrb), [0, 2]);
+        assert_eq!(spawned(&mut app, ChunkItemKind::Asteroid), [1, 2]);
+    }
+}
@@ -0,0 +1,115 @@
+//! An asteroid on a collision course: grazing it earns aura, ramming it wears the hull
+//! and the aura stops flowing.
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::Asteroid,
+    player::{Player, hull::Hull},
+    screens::Screen,
+    simulation::Simulation,
+};
+
+use super::{TestScene, count, no_drive, ship, ship_aura, ship_position};
+
+pub const SCENE: TestScene = TestScene {
+    name: "asteroid_impact",
+    description: "ramming an asteroid destroys it, wears the hull and ends the aura earned by grazing it",
+    setup,
+    drive: no_drive,
+    check,
+};
+
+/// Slow enough for the hull to survive the impact.
+const SHIP_SPEED: f32 = 300.0;
+/// Straight ahead of the ship, reached in about half a second.
+const ASTEROID_DISTANCE: f32 = 150.0;
+const ASTEROID_RADIUS: f32 = 20.0;
+/// The scene fails if the ship didn't hit the asteroid by then.
+const MAX_TICKS: u32 = 120;
+/// Ticks the aura is watched for after the impact, it only trickles in at 1 point per second.
+const AFTER_IMPACT_TICKS: u32 = 32;
+
+fn setup(world: &mut World) {
+    let start = ship_position(world);
+    ship(world).insert(LinearVelocity(Vec2::Y * SHIP_SPEED));
+
+    world.spawn((
+        Asteroid {
+            pos: (start + Vec2::Y * ASTEROID_DISTANCE).extend(0.0),
+            radius: ASTEROID_RADIUS,
+            velocity: Vec2::ZERO,
+            spin: 0.0,
+        },
+        DespawnOnExit(Screen::Gameplay),
+    ));
+}
+
+fn near_asteroids(world: &mut World) -> bool {
+    ship(world)
+        .get::<Player>()
+        .is_some_and(|player| player.near_asteroids)
+}
+
+fn hull_damaged(world: &mut World) -> bool {
+    ship(world)
+        .get::<Hull>()
+        .is_some_and(|hull| hull.hp < hull.max_hp)
+}
+
+fn check(sim: &mut Simulation) -> Result<(), String> {
+    let start_aura = ship_aura(sim.world_mut());
+
+    let mut grazed = false;
+    let mut hit = false;
+    for _ in 0..MAX_TICKS {
+        sim.run_ticks(1);
+        grazed |= near_asteroids(sim.world_mut());
+        if hull_damaged(sim.world_mut()) {
+            hit = true;
+            break;
+        }
+    }
+
+    if !hit {
+        return Err(format!(
+            "the ship didn't hit the asteroid in {MAX_TICKS} ticks"
+        ));
+    }
+    if !grazed {
+        return Err("the ship never flew close to the asteroid".to_string());
+    }
+    let impact_aura = ship_aura(sim.world_mut());
+    if impact_aura - start_aura < 5.0 {
+        return Err(format!(
+            "grazing the asteroid earned {:.1} aura",
+            impact_aura - start_aura
+        ));
+    }
+    if count::<Asteroid>(sim.world_mut()) > 0 {
+        return Err("the rammed asteroid is still there".to_string());
+    }
+
+    sim.run_ticks(AFTER_IMPACT_TICKS);
+    if sim.is_dead() {
+        return Err("the impact killed the ship".to_string());
+    }
+    if near_asteroids(sim.world_mut()) {
+        return Err("the ship still counts as grazing an asteroid".to_string());
+    }
+    // only the trickle is left, grazing would earn far more at this speed
+    let trickle = AFTER_IMPACT_TICKS as f32
+        * sim
+            .world()
+            .resource::<Time<Fixed>>()
+            .timestep()
+            .as_secs_f32();
+    let earned = ship_aura(sim.world_mut()) - impact_aura;
+    if earned > trickle + 0.05 {
+        return Err(format!(
+            "the ship kept earning aura after the impact, {earned:.2} instead of {trickle:.2}"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! The ship spends its aura on bullet time, the physics slow down until it runs out.
+
+use avian2d::prelude::{Physics, PhysicsTime};
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, NextTickInput, TickInput},
+    player::{
+        Player,
+        abilities::{BULLET_TIME_AURA_COST, BULLET_TIME_DURATION, BULLET_TIME_SPEED},
+    },
+    simulation::Simulation,
+};
+
+use super::{TestScene, ship, ship_aura, ship_position};
+
+pub const SCENE: TestScene = TestScene {
+    name: "bullet_time",
+    description: "bullet time costs aura, slows the physics down and ends on time",
+    setup,
+    drive,
+    check,
+};
+
+/// Enough for one bullet time.
+const START_AURA: f32 = 150.0;
+const PRESS_TICK: u32 = 16;
+/// Ticks the distance flown is measured over, before and during bullet time.
+const SAMPLE_TICKS: u32 = 8;
+
+fn setup(world: &mut World) {
+    if let Some(mut player) = ship(world).get_mut::<Player>() {
+        player.aura_points = START_AURA;
+    }
+}
+
+fn drive(tick: u32, world: &mut World) {
+    if tick == PRESS_TICK {
+        world.resource_mut::<NextTickInput>().0 = TickInput {
+            pressed: vec![Action::BulletTime],
+            turn: 0.0,
+        };
+    }
+}
+
+fn physics_speed(sim: &Simulation) -> f32 {
+    sim.world().resource::<Time<Physics>>().relative_speed()
+}
+
+/// Distance the ship flies over the ticks.
+fn fly(sim: &mut Simulation, ticks: u32) -> f32 {
+    let from = ship_position(sim.world_mut());
+    sim.run_ticks(ticks);
+    ship_position(sim.world_mut()).distance(from)
+}
+
+fn check(sim: &mut Simulation) -> Result<(), String> {
+    sim.run_ticks(PRESS_TICK - SAMPLE_TICKS);
+    let normal = fly(sim, SAMPLE_TICKS);
+
+    let aura = ship_aura(sim.world_mut());
+    sim.run_ticks(1);
+    let speed = physics_speed(sim);
+    if speed != BULLET_TIME_SPEED {
+        return Err(format!(
+            "bullet time didn't start, the physics run at {speed}x"
+        ));
+    }
+    let spent = aura - ship_aura(sim.world_mut());
+    if spent < BULLET_TIME_AURA_COST - 1.0 {
+        return Err(format!(
+            "bullet time cost {spent:.1} aura instead of {BULLET_TIME_AURA_COST}"
+        ));
+    }
+
+    let slow = fly(sim, SAMPLE_TICKS);
+    if slow > normal * BULLET_TIME_SPEED * 2.0 {
+        return Err(format!(
+            "the ship didn't slow down, it flew {slow:.1} in {SAMPLE_TICKS} ticks and {normal:.1} before"
+        ));
+    }
+
+    // bullet time counts down on the fixed clock
+    let timestep = sim.world().resource::<Time<Fixed>>().timestep();
+    let duration = (BULLET_TIME_DURATION / timestep.as_secs_f32()).round() as u32;
+    sim.run_ticks(duration - SAMPLE_TICKS - 4);
+    if physics_speed(sim) != BULLET_TIME_SPEED {
+        return Err("bullet time ended early".to_string());
+    }
+    sim.run_ticks(8);
+    if physics_speed(sim) != 1.0 {
+        return Err(format!(
+            "bullet time didn't end after {BULLET_TIME_DURATION} s, the physics run at {}x",
+            physics_speed(sim)
+        ));
+    }
+    Ok(())
+}
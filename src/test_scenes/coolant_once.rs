@@ -0,0 +1,86 @@
+//! The ship flies over a single coolant orb, which never burns away: it must be taken in only once.
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{
+    player::movement::CurrentGas,
+    screens::Screen,
+    simulation::Simulation,
+    space::gas::{GasOrb, Spent, kind::GasKind},
+};
+
+use super::{TestScene, no_drive, ship, ship_position};
+
+pub const SCENE: TestScene = TestScene {
+    name: "coolant_once",
+    description: "a gas orb gives its gas once, even coolant that never burns",
+    setup,
+    drive: no_drive,
+    check,
+};
+
+const SHIP_SPEED: f32 = 150.0;
+/// Ahead of the ship, reached once the orb is in the spatial index of the gas.
+const ORB_OFFSET: Vec2 = Vec2::new(1.0, 150.0);
+const ORB_MASS: f32 = 0.3;
+/// The scene fails if the ship didn't take the orb in by then.
+const MAX_TICKS: u32 = 160;
+/// Ticks the orb stays under the ship after it was taken in, plenty to take it in again.
+const LINGER_TICKS: u32 = 16;
+
+fn setup(world: &mut World) {
+    let start = ship_position(world);
+    ship(world).insert((LinearVelocity(Vec2::Y * SHIP_SPEED), CurrentGas(0.0)));
+
+    world.spawn((
+        GasOrb(ORB_MASS),
+        GasKind::Coolant,
+        Transform::from_translation((start + ORB_OFFSET).extend(0.0)),
+        DespawnOnExit(Screen::Gameplay),
+    ));
+}
+
+fn current_gas(world: &mut World) -> f32 {
+    ship(world).get::<CurrentGas>().map_or(0.0, |gas| gas.0)
+}
+
+fn is_spent(world: &mut World) -> bool {
+    world
+        .query_filtered::<(), (With<GasOrb>, With<Spent>)>()
+        .iter(world)
+        .next()
+        .is_some()
+}
+
+fn check(sim: &mut Simulation) -> Result<(), String> {
+    let mut max_gas = 0.0f32;
+    let mut taken_at = None;
+    for tick in 0..MAX_TICKS {
+        sim.run_ticks(1);
+        max_gas = max_gas.max(current_gas(sim.world_mut()));
+        if taken_at.is_none() && is_spent(sim.world_mut()) {
+            taken_at = Some(tick);
+        }
+        if taken_at.is_some_and(|taken_at| tick >= taken_at + LINGER_TICKS) {
+            break;
+        }
+    }
+
+    if taken_at.is_none() {
+        return Err(format!(
+            "the ship didn't take the orb in within {MAX_TICKS} ticks"
+        ));
+    }
+    if max_gas < ORB_MASS * 0.5 {
+        return Err(format!(
+            "the orb gave {max_gas:.2} gas, less than half of its {ORB_MASS}"
+        ));
+    }
+    if max_gas > ORB_MASS + 0.001 {
+        return Err(format!(
+            "the orb gave {max_gas:.2} gas, more than its {ORB_MASS}"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! The ship flies straight into a line of gas orbs, it must burn the gas behind it and ride the boost.
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{
+    player::movement::CurrentGas,
+    screens::Screen,
+    simulation::Simulation,
+    space::gas::{BurningGasOrb, GasOrb, kind::GasKind},
+};
+
+use super::{TestScene, count, no_drive, ship, ship_position, ship_speed};
+
+pub const SCENE: TestScene = TestScene {
+    name: "gas_corridor",
+    description: "the ship ignites the gas it flies through and the burning gas speeds it up",
+    setup,
+    drive: no_drive,
+    check,
+};
+
+/// Full speed, the ship outruns the flames it leaves behind.
+const SHIP_SPEED: f32 = 900.0;
+/// The corridor starts this far ahead, so the ship first coasts through empty space for comparison.
+const CORRIDOR_START: f32 = 450.0;
+const CORRIDOR_LENGTH: f32 = 900.0;
+const ORB_SPACING: f32 = 6.0;
+const ORB_MASS: f32 = 0.3;
+/// Ticks the speed is measured over, in empty space and in the corridor.
+const SAMPLE_TICKS: u32 = 24;
+
+fn setup(world: &mut World) {
+    let start = ship_position(world);
+    ship(world).insert((LinearVelocity(Vec2::Y * SHIP_SPEED), CurrentGas(0.0)));
+
+    let orbs = (CORRIDOR_LENGTH / ORB_SPACING) as u32;
+    for i in 0..orbs {
+        let pos = start + Vec2::Y * (CORRIDOR_START + i as f32 * ORB_SPACING);
+        world.spawn((
+            GasOrb(ORB_MASS),
+            GasKind::Standard,
+            Transform::from_translation(pos.extend(0.0)),
+            DespawnOnExit(Screen::Gameplay),
+        ));
+    }
+}
+
+fn check(sim: &mut Simulation) -> Result<(), String> {
+    // empty space, only the engine pushes
+    sim.run_ticks(4);
+    let coast_start = ship_speed(sim.world_mut());
+    sim.run_ticks(SAMPLE_TICKS);
+    let coast_loss = coast_start - ship_speed(sim.world_mut());
+
+    // well inside the corridor
+    sim.run_ticks(12);
+    let boost_start = ship_speed(sim.world_mut());
+    let mut max_gas = 0.0f32;
+    let mut max_burning = 0;
+    for _ in 0..SAMPLE_TICKS {
+        sim.run_ticks(1);
+        let gas = ship(sim.world_mut())
+            .get::<CurrentGas>()
+            .map_or(0.0, |gas| gas.0);
+        max_gas = max_gas.max(gas);
+        max_burning = max_burning.max(count::<BurningGasOrb>(sim.world_mut()));
+    }
+    let boost_loss = boost_start - ship_speed(sim.world_mut());
+
+    if sim.is_dead() {
+        return Err("the ship died in the corridor".to_string());
+    }
+    if max_gas < 0.5 {
+        return Err(format!(
+            "the ship collected little gas, {max_gas:.2} at most"
+        ));
+    }
+    if max_burning == 0 {
+        return Err("the gas behind the ship didn't catch fire".to_string());
+    }
+    if boost_loss > coast_loss * 0.5 {
+        return Err(format!(
+            "the gas didn't boost the ship, it lost {boost_loss:.1} speed in the corridor and {coast_loss:.1} in empty space"
+        ));
+    }
+    Ok(())
+}
@@ -1,5 +1,264 @@
-// todo: maybe better create a whole separate `App` for it
+//! Hand-built scenes checking the core mechanics.
+//!
+//! A scene starts a run in a world that generates nothing, builds a situation around the ship
+//! and drives it tick by tick, see [`TestScene`].
+//!
+//! `cargo run -- --test-scenes [name...]` plays the scenes (all of them when no name is given) in a
+//! headless [`Simulation`] and checks how they went. The exit code is an error if any of them failed,
+//! so they run as automated tests.
+//! In dev builds, `cargo run -- --scene <name>` starts every run of the game on the scene instead,
+//! to watch it play out.
 
-use bevy::*;
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
 
+use crate::{
+    input::TickInputSystems,
+    player::{Player, death::player_alive, spawn::spawn_player},
+    screens::Screen,
+    simulation::Simulation,
+    space::{WorldSeed, config::WorldGenConfig, reset_world},
+};
 
+mod asteroid_impact;
+mod bullet_time;
+mod coolant_once;
+mod gas_corridor;
+mod red_orb_chain;
+
+/// The world is empty, the seed only shapes the gas noise the ship glides on.
+const SEED: WorldSeed = WorldSeed(0);
+
+/// All the scenes, in the order `--test-scenes` plays them.
+pub const SCENES: [&TestScene; 5] = [
+    &gas_corridor::SCENE,
+    &coolant_once::SCENE,
+    &red_orb_chain::SCENE,
+    &asteroid_impact::SCENE,
+    &bullet_time::SCENE,
+];
+
+/// Plays the scene given with `--scene <name>` instead of generated runs.
+#[cfg(feature = "dev")]
+pub(super) fn plugin(app: &mut App) {
+    if let Some(scene) = scene_from_args() {
+        app.insert_resource(ActiveScene::new(scene));
+        add_scene_systems(app);
+    }
+}
+
+/// A situation set up by hand, and how it must play out.
+pub struct TestScene {
+    /// Selects the scene on the command line.
+    pub name: &'static str,
+    /// What the scene checks.
+    pub description: &'static str,
+    /// Builds the situation once the ship is spawned.
+    setup: fn(&mut World),
+    /// Called at the start of every tick with its number, before the tick input is applied.
+    /// It can press the controls by overwriting [`crate::input::NextTickInput`], or trigger events.
+    drive: fn(u32, &mut World),
+    /// Plays the scene tick by tick, the error describes the first check that failed.
+    check: fn(&mut Simulation) -> Result<(), String>,
+}
+
+impl TestScene {
+    /// Plays the scene in a headless simulation.
+    pub fn play(&'static self) -> Result<(), String> {
+        let mut simulation = Simulation::with_plugins(SEED, move |app: &mut App| {
+            app.insert_resource(ActiveScene::new(self));
+            add_scene_systems(app);
+        });
+        (self.check)(&mut simulation)
+    }
+}
+
+/// The scene played instead of a generated run.
+#[derive(Resource)]
+pub struct ActiveScene {
+    pub scene: &'static TestScene,
+    /// Ticks driven since the run started.
+    tick: u32,
+}
+
+impl ActiveScene {
+    pub fn new(scene: &'static TestScene) -> Self {
+        Self { scene, tick: 0 }
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static TestScene> {
+    SCENES.into_iter().find(|scene| scene.name == name)
+}
+
+fn scene_names() -> String {
+    SCENES.map(|scene| scene.name).join(", ")
+}
+
+fn add_scene_systems(app: &mut App) {
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        start_scene.after(spawn_player).after(reset_world),
+    )
+    .add_systems(
+        FixedFirst,
+        drive_scene
+            .in_set(TickInputSystems::Script)
+            .run_if(in_state(Screen::Gameplay))
+            .run_if(player_alive),
+    );
+}
+
+/// Empties the world and builds the scene around the freshly spawned ship.
+fn start_scene(world: &mut World) {
+    world.insert_resource(WorldGenConfig::empty());
+
+    let mut active = world.resource_mut::<ActiveScene>();
+    active.tick = 0;
+    let setup = active.scene.setup;
+    setup(world);
+}
+
+fn drive_scene(world: &mut World) {
+    let mut active = world.resource_mut::<ActiveScene>();
+    let (drive, tick) = (active.scene.drive, active.tick);
+    active.tick += 1;
+    drive(tick, world);
+}
+
+/// For scenes that play out without any help.
+fn no_drive(_tick: u32, _world: &mut World) {}
+
+/// The ship, the scenes are built around it.
+fn ship(world: &mut World) -> EntityWorldMut<'_> {
+    let entity = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .expect("the ship is spawned with the run");
+    world.entity_mut(entity)
+}
+
+fn ship_position(world: &mut World) -> Vec2 {
+    ship(world)
+        .get::<Transform>()
+        .map_or(Vec2::ZERO, |tr| tr.translation.truncate())
+}
+
+fn ship_speed(world: &mut World) -> f32 {
+    ship(world)
+        .get::<LinearVelocity>()
+        .map_or(0.0, |velocity| velocity.length())
+}
+
+fn ship_aura(world: &mut World) -> f32 {
+    ship(world)
+        .get::<Player>()
+        .map_or(0.0, |player| player.aura_points)
+}
+
+/// Number of entities with the component.
+fn count<T: Component>(world: &mut World) -> usize {
+    world.query_filtered::<(), With<T>>().iter(world).count()
+}
+
+/// The scene where `--scene <name>` was given, logs an error for an unknown name.
+#[cfg(feature = "dev")]
+fn scene_from_args() -> Option<&'static TestScene> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--scene").skip(1);
+    let Some(name) = args.next() else {
+        if std::env::args().any(|arg| arg == "--scene") {
+            error!(
+                "missing scene after `--scene`, the scenes are: {}",
+                scene_names()
+            );
+        }
+        return None;
+    };
+
+    let scene = find(&name);
+    if scene.is_none() {
+        error!("unknown scene `{name}`, the scenes are: {}", scene_names());
+    }
+    scene
+}
+
+/// Plays the scenes named after `--test-scenes`, or all of them, and prints which ones failed.
+/// `args` is the command line without the program name, its other arguments are scene names.
+pub fn run(args: impl IntoIterator<Item = String>) -> AppExit {
+    let mut scenes = vec![];
+    for arg in args {
+        if arg == "--test-scenes" {
+            continue;
+        }
+        let Some(scene) = find(&arg) else {
+            eprintln!(
+                "test scenes failed: unknown scene `{arg}`, the scenes are: {}",
+                scene_names()
+            );
+            return AppExit::error();
+        };
+        scenes.push(scene);
+    }
+    if scenes.is_empty() {
+        scenes = SCENES.to_vec();
+    }
+
+    let mut failed = 0;
+    for scene in &scenes {
+        match scene.play() {
+            Ok(()) => println!("{}: ok", scene.name),
+            Err(err) => {
+                failed += 1;
+                println!("{}: FAILED, {err}", scene.name);
+                println!("  ({})", scene.description);
+            }
+        }
+    }
+    println!(
+        "{} of {} scenes passed",
+        scenes.len() - failed,
+        scenes.len()
+    );
+
+    if failed == 0 {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_plays(scene: &'static TestScene) {
+        if let Err(err) = scene.play() {
+            panic!("{} failed, {err} ({})", scene.name, scene.description);
+        }
+    }
+
+    #[test]
+    fn gas_corridor() {
+        assert_plays(&gas_corridor::SCENE);
+    }
+
+    #[test]
+    fn coolant_once() {
+        assert_plays(&coolant_once::SCENE);
+    }
+
+    #[test]
+    fn red_orb_chain() {
+        assert_plays(&red_orb_chain::SCENE);
+    }
+
+    #[test]
+    fn asteroid_impact() {
+        assert_plays(&asteroid_impact::SCENE);
+    }
+
+    #[test]
+    fn bullet_time() {
+        assert_plays(&bullet_time::SCENE);
+    }
+}
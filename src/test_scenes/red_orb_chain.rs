@@ -0,0 +1,90 @@
+//! A burn front reaches a single red orb, its explosion must set off the red orbs around it.
+
+use bevy::prelude::*;
+
+use crate::{
+    red_gas::{RedGasOrb, RedOrbExplosion},
+    screens::Screen,
+    simulation::Simulation,
+    space::gas::burn::BurnEvent,
+};
+
+use super::{TestScene, count, ship_position};
+
+pub const SCENE: TestScene = TestScene {
+    name: "red_orb_chain",
+    description: "a red orb lit by the fire explodes and its explosion sets off the other red orbs",
+    setup,
+    drive,
+    check,
+};
+
+const ORB_RADIUS: f32 = 5.0;
+/// Relative to the ship, behind it so it flies away from the explosions.
+const LIT_ORB: Vec2 = Vec2::new(0.0, -300.0);
+/// Out of reach of the fire, only an explosion can set them off.
+const CHAINED_ORBS: [Vec2; 2] = [Vec2::new(150.0, -300.0), Vec2::new(300.0, -300.0)];
+/// The red orbs must be in their spatial index before the fire starts, it refreshes every 0.1 s.
+const IGNITION_TICK: u32 = 16;
+/// Long enough for the fire to reach the lit orb, too short for its explosion to reach the others.
+const FIRE_TICKS: u32 = 6;
+/// The explosions take about a second to cover the chained orbs.
+const CHAIN_TICKS: u32 = 160;
+
+/// The red orb the fire is started on.
+#[derive(Component)]
+struct LitOrb;
+
+fn setup(world: &mut World) {
+    let start = ship_position(world);
+
+    let orb = |offset: Vec2| {
+        (
+            RedGasOrb {
+                radius: ORB_RADIUS,
+                pos: (start + offset).extend(0.0),
+            },
+            DespawnOnExit(Screen::Gameplay),
+        )
+    };
+    world.spawn((orb(LIT_ORB), LitOrb));
+    for offset in CHAINED_ORBS {
+        world.spawn(orb(offset));
+    }
+}
+
+fn drive(tick: u32, world: &mut World) {
+    if tick != IGNITION_TICK {
+        return;
+    }
+    let Ok(orb) = world
+        .query_filtered::<&RedGasOrb, With<LitOrb>>()
+        .single(world)
+    else {
+        return;
+    };
+    let pos = orb.pos.truncate();
+    world.write_message(BurnEvent { pos });
+}
+
+fn check(sim: &mut Simulation) -> Result<(), String> {
+    sim.run_ticks(IGNITION_TICK + FIRE_TICKS);
+    let explosions = count::<RedOrbExplosion>(sim.world_mut());
+    if explosions != 1 {
+        return Err(format!(
+            "the fire should set off the one red orb it reached, {explosions} exploded"
+        ));
+    }
+
+    sim.run_ticks(CHAIN_TICKS);
+    let left = count::<RedGasOrb>(sim.world_mut());
+    if left > 0 {
+        return Err(format!("the chain reaction stopped, {left} red orbs left"));
+    }
+    let explosions = count::<RedOrbExplosion>(sim.world_mut());
+    let expected = CHAINED_ORBS.len() + 1;
+    if explosions != expected {
+        return Err(format!("{explosions} explosions instead of {expected}"));
+    }
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::{AudioChannel, AudioControl};
 
-use crate::audio::AudioAssets;
+use crate::{
+    audio::{AudioAssets, UiChannel, linear_to_decibels},
+    settings::Settings,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
@@ -40,30 +43,36 @@ fn apply_interaction_palette(
 
 fn play_on_hover_sound_effect(
     trigger: On<Pointer<Over>>,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<UiChannel>>,
     audio_assets: Option<Res<AudioAssets>>,
+    settings: Res<Settings>,
     interaction_query: Query<(), With<Interaction>>,
 ) {
     let Some(audio_assets) = audio_assets else {
         return;
     };
     if interaction_query.contains(trigger.event().event_target()) {
-        audio.play(audio_assets.button_hover.clone());
+        audio
+            .play(audio_assets.button_hover.clone())
+            .with_volume(linear_to_decibels(settings.sfx_gain()));
         // commands.spawn(sound_effect(audio_assets.hover.clone()));
     }
 }
 
 fn play_on_click_sound_effect(
     trigger: On<Pointer<Click>>,
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<UiChannel>>,
     audio_assets: Option<Res<AudioAssets>>,
+    settings: Res<Settings>,
     interaction_query: Query<(), With<Interaction>>,
 ) {
     let Some(audio_assets) = audio_assets else {
         return;
     };
     if interaction_query.contains(trigger.event().event_target()) {
-        audio.play(audio_assets.button_click.clone());
+        audio
+            .play(audio_assets.button_click.clone())
+            .with_volume(linear_to_decibels(settings.sfx_gain()));
         // commands.spawn(sound_effect(audio_assets.click.clone()));
     }
 }
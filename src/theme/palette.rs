@@ -6,6 +6,9 @@ pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 /// #fcfbcc
 pub const HEADER_TEXT: Color = Color::srgb(0.988, 0.984, 0.800);
 
+/// #7fe0d8
+pub const HIGHLIGHTED_TEXT: Color = Color::srgb(0.498, 0.878, 0.847);
+
 /// #ececec
 pub const BUTTON_TEXT: Color = Color::srgb(0.925, 0.925, 0.925);
 /// #4666bf
@@ -68,6 +68,16 @@ pub fn label(text: impl Into<String>) -> impl Bundle {
     )
 }
 
+/// A text label standing out from the others, e.g. the current entry of a list.
+pub fn highlighted_label(text: impl Into<String>) -> impl Bundle {
+    (
+        Name::new("Highlighted Label"),
+        Text(text.into()),
+        TextFont::from_font_size(24.0),
+        TextColor(HIGHLIGHTED_TEXT),
+    )
+}
+
 pub fn emoji_label(text: impl Into<String>, hud_assets: &HudAssets) -> impl Bundle {
     (
         Name::new("Emoji Label"),
@@ -102,6 +112,27 @@ where
     )
 }
 
+/// A medium rounded button with text and an action defined as an [`Observer`], for lists of options.
+pub fn button_medium<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: EntityEvent,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        action,
+        Node {
+            width: Px(260.0),
+            height: Px(50.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            border_radius: BorderRadius::MAX,
+            ..default()
+        },
+    )
+}
+
 /// A small square button with text and an action defined as an [`Observer`].
 pub fn button_small<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where
@@ -1,6 +1,8 @@
-use bevy::{prelude::*, window::PresentMode};
+use bevy::prelude::*;
 use bevy_tweening::{AnimationSystem, Lens};
 
+use crate::settings::Settings;
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct StandardMaterialLens {
     pub color_start: Color,
@@ -33,10 +35,7 @@ impl Lens<PointLight> for PointLightLens {
     }
 }
 
-pub fn toggle_vsync(mut window: Single<&mut Window>) {
-    window.present_mode = match window.present_mode {
-        PresentMode::AutoVsync => PresentMode::AutoNoVsync,
-        PresentMode::AutoNoVsync => PresentMode::AutoVsync,
-        _ => panic!(),
-    };
+/// Toggles vsync without saving it, the settings menu saves it.
+pub fn toggle_vsync(mut settings: ResMut<Settings>) {
+    settings.vsync = !settings.vsync;
 }
@@ -3,6 +3,8 @@ use rand::{Rng, RngExt as _};
 
 use bevy::{post_process::bloom::Bloom, prelude::*};
 
+use crate::settings::Settings;
+
 pub const BASE_BLOOM: Bloom = Bloom::NATURAL;
 
 pub fn screen_shake_plugin(app: &mut App) {
@@ -28,11 +30,12 @@ pub fn screen_shake(
     time: Res<Time<Physics>>,
     mut screen_shake: ResMut<ScreenShake>,
     mut query: Query<&mut Transform, With<Camera>>,
+    settings: Res<Settings>,
 ) {
     if time.elapsed_secs() < screen_shake.until {
         // * maybe tweak these
-        screen_shake.max_angle = 0.5;
-        screen_shake.max_offset = 500.0;
+        screen_shake.max_angle = 0.5 * settings.screen_shake;
+        screen_shake.max_offset = 500.0 * settings.screen_shake;
         screen_shake.trauma = (screen_shake.trauma + 1.0 * time.delta_secs()).clamp(0.0, 1.0);
         screen_shake.last_position = Vec2::new(0.0, 0.0);
     }